@@ -0,0 +1,107 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Serialize, Deserialize};
+use std::fmt::Display;
+use std::str::FromStr;
+use tabled::Tabled;
+
+/// Day without work that still counts towards the expected hours (e.g. vacation)
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Tabled, Serialize, Deserialize)]
+pub struct Absence {
+    #[tabled(display_with = "display_date")]
+    pub date: NaiveDate,
+
+    pub kind: AbsenceKind,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[tabled(display_with = "display_option")]
+    pub comment: Option<String>,
+}
+
+/// Reason of an [Absence]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum AbsenceKind {
+    Vacation,
+    Sick,
+    Holiday,
+
+    /// Any other reason
+    Other(String),
+}
+
+/// Vacation days taken in a year compared to the yearly quota
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct VacationSummary {
+    pub year: i32,
+    pub quota: Option<u32>,
+    pub taken: u32,
+
+    /// Days of the quota not taken yet (negative if exceeded)
+    pub remaining: Option<i64>,
+}
+
+impl Absence {
+    pub fn new(date: NaiveDate, kind: AbsenceKind, comment: Option<String>) -> Self {
+        Absence { date, kind, comment }
+    }
+}
+
+impl VacationSummary {
+    /// Count the vacation days of `year` in `absences`
+    pub fn create(year: i32, absences: &[Absence], quota: Option<u32>) -> Self {
+        let taken = absences.iter()
+            .filter(|a| a.kind == AbsenceKind::Vacation && a.date.year() == year)
+            .count() as u32;
+        VacationSummary {
+            year, quota, taken,
+            remaining: quota.map(|q| q as i64 - taken as i64),
+        }
+    }
+}
+
+impl Display for AbsenceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbsenceKind::Vacation => write!(f, "vacation"),
+            AbsenceKind::Sick => write!(f, "sick"),
+            AbsenceKind::Holiday => write!(f, "holiday"),
+            AbsenceKind::Other(kind) => write!(f, "{}", kind),
+        }
+    }
+}
+
+impl FromStr for AbsenceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" => Err("The kind of an absence must not be empty".to_owned()),
+            "vacation" => Ok(AbsenceKind::Vacation),
+            "sick" => Ok(AbsenceKind::Sick),
+            "holiday" => Ok(AbsenceKind::Holiday),
+            other => Ok(AbsenceKind::Other(other.to_owned())),
+        }
+    }
+}
+
+impl TryFrom<String> for AbsenceKind {
+    type Error = String;
+
+    fn try_from(kind: String) -> Result<Self, Self::Error> {
+        kind.parse()
+    }
+}
+
+impl From<AbsenceKind> for String {
+    fn from(kind: AbsenceKind) -> Self {
+        kind.to_string()
+    }
+}
+
+fn display_date(date: &NaiveDate) -> String {
+    date.format("%Y-%m-%d %a").to_string()
+}
+
+fn display_option(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
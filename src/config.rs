@@ -0,0 +1,203 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::storage::{write_atomic, XDG_DIR_PREFIX};
+
+/// Name of the configuration file in the XDG config directory
+const CONFIG_FILE_NAME: &str = "config.yaml";
+
+/// Keys that can only be set globally
+const GLOBAL_KEYS: [&str; 2] = ["namespace", "single_clock"];
+
+/// Keys that can be set globally and per namespace
+const NAMESPACE_KEYS: [&str; 4] = ["tail", "wrap", "git_commit", "git_push"];
+
+/// Defaults of the command line, read from `$XDG_CONFIG_HOME/foliot/config.yaml`
+///
+/// Keys are addressed like `tail` or, for a single namespace, `namespaces.<namespace>.tail`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+    /// Namespace to use without `--namespace`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+
+    /// Forbid running clocks in more than one namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub single_clock: Option<bool>,
+
+    #[serde(flatten)]
+    pub defaults: Defaults,
+
+    /// Defaults that differ for single namespaces
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub namespaces: BTreeMap<String, Defaults>,
+}
+
+/// Defaults that can be overridden per namespace
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Defaults {
+    /// Number of entries shown by `show` and `summarize`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tail: Option<usize>,
+
+    /// Width to wrap the columns of `show` at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap: Option<usize>,
+
+    /// Commit changes to the git repository in the data directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<bool>,
+
+    /// Pull and push the git repository after committing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_push: Option<bool>,
+}
+
+impl Config {
+    /// Read the configuration file (an empty configuration if there is none)
+    pub fn load() -> Result<Self, String> {
+        let path = match Self::xdg_dirs()?.find_config_file(CONFIG_FILE_NAME) {
+            Some(path) => path,
+            None => return Ok(Config::default()),
+        };
+        let content = fs::read(&path)
+            .map_err(|e| e.to_string())?;
+        serde_yaml::from_slice(&content)
+            .map_err(|e| format!("Invalid configuration in {}: {}", path.to_string_lossy(), e))
+    }
+
+    /// Write the configuration file
+    pub fn save(&self) -> Result<(), String> {
+        let content = serde_yaml::to_string(self)
+            .map_err(|e| e.to_string())?;
+        write_atomic(&self.path()?, content.as_bytes())
+    }
+
+    /// Path to the configuration file
+    pub fn path(&self) -> Result<PathBuf, String> {
+        Self::xdg_dirs()?.place_config_file(CONFIG_FILE_NAME)
+            .map_err(|e| e.to_string())
+    }
+
+    fn xdg_dirs() -> Result<xdg::BaseDirectories, String> {
+        xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Defaults for `namespace`, falling back to the global ones
+    pub fn defaults(&self, namespace: &str) -> Defaults {
+        let overrides = self.namespaces.get(namespace).cloned().unwrap_or_default();
+        Defaults {
+            tail: overrides.tail.or(self.defaults.tail),
+            wrap: overrides.wrap.or(self.defaults.wrap),
+            git_commit: overrides.git_commit.or(self.defaults.git_commit),
+            git_push: overrides.git_push.or(self.defaults.git_push),
+        }
+    }
+
+    /// Value of a key, if it is set
+    pub fn get(&self, key: &str) -> Result<Option<String>, String> {
+        let value = match parse_key(key)? {
+            (None, "namespace") => self.namespace.clone(),
+            (None, "single_clock") => self.single_clock.map(|v| v.to_string()),
+            (None, key) => self.defaults.get(key),
+            (Some(namespace), key) => self.namespaces.get(namespace).and_then(|d| d.get(key)),
+        };
+        Ok(value)
+    }
+
+    /// Set a key to a value, given as it would be on the command line
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match parse_key(key)? {
+            (None, "namespace") if value.is_empty() => return Err("The namespace must not be empty".to_owned()),
+            (None, "namespace") => self.namespace = Some(value.to_owned()),
+            (None, "single_clock") => self.single_clock = Some(parse_value(key, value)?),
+            (None, key) => self.defaults.set(key, Some(value))?,
+            (Some(namespace), key) => self.namespaces.entry(namespace.to_owned()).or_default().set(key, Some(value))?,
+        }
+        Ok(())
+    }
+
+    /// Remove a key, so its default applies again
+    pub fn unset(&mut self, key: &str) -> Result<(), String> {
+        match parse_key(key)? {
+            (None, "namespace") => self.namespace = None,
+            (None, "single_clock") => self.single_clock = None,
+            (None, key) => self.defaults.set(key, None)?,
+            (Some(namespace), key) => {
+                if let Some(defaults) = self.namespaces.get_mut(namespace) {
+                    defaults.set(key, None)?;
+                    if *defaults == Defaults::default() {
+                        self.namespaces.remove(namespace);
+                    }
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// All keys that are set with their values
+    pub fn list(&self) -> Vec<(String, String)> {
+        let mut keys: Vec<String> = GLOBAL_KEYS.iter().chain(NAMESPACE_KEYS.iter())
+            .map(|k| k.to_string())
+            .collect();
+        for namespace in self.namespaces.keys() {
+            keys.extend(NAMESPACE_KEYS.iter().map(|k| format!("namespaces.{}.{}", namespace, k)));
+        }
+        keys.into_iter()
+            .filter_map(|k| self.get(&k).ok().flatten().map(|v| (k, v)))
+            .collect()
+    }
+}
+
+impl Defaults {
+    fn get(&self, key: &str) -> Option<String> {
+        match key {
+            "tail" => self.tail.map(|v| v.to_string()),
+            "wrap" => self.wrap.map(|v| v.to_string()),
+            "git_commit" => self.git_commit.map(|v| v.to_string()),
+            "git_push" => self.git_push.map(|v| v.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Set or, with `None`, remove a key
+    fn set(&mut self, key: &str, value: Option<&str>) -> Result<(), String> {
+        match key {
+            "tail" => self.tail = value.map(|v| parse_value(key, v)).transpose()?,
+            "wrap" => self.wrap = value.map(|v| parse_value(key, v)).transpose()?,
+            "git_commit" => self.git_commit = value.map(|v| parse_value(key, v)).transpose()?,
+            "git_push" => self.git_push = value.map(|v| parse_value(key, v)).transpose()?,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+}
+
+/// Split a key into the namespace it applies to (if any) and the name of the setting
+fn parse_key(key: &str) -> Result<(Option<&str>, &str), String> {
+    if let Some(rest) = key.strip_prefix("namespaces.") {
+        return match rest.rsplit_once('.') {
+            Some((namespace, name)) if !namespace.is_empty() && NAMESPACE_KEYS.contains(&name) => Ok((Some(namespace), name)),
+            _ => Err(unknown_key(key)),
+        };
+    }
+    if GLOBAL_KEYS.contains(&key) || NAMESPACE_KEYS.contains(&key) {
+        Ok((None, key))
+    } else {
+        Err(unknown_key(key))
+    }
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse()
+        .map_err(|_| format!("Invalid value '{}' for '{}'", value, key))
+}
+
+fn unknown_key(key: &str) -> String {
+    format!("Unknown configuration key '{}' (expected one of {}, {} or namespaces.<namespace>.<key>)",
+            key, GLOBAL_KEYS.join(", "), NAMESPACE_KEYS.join(", "))
+}
@@ -0,0 +1,82 @@
+use serde::Serializer;
+use std::fmt::Display;
+use std::iter::Sum;
+use std::ops::{Add, Sub};
+
+/// Exact duration, displayed in hours and minutes
+///
+/// Seconds are kept, so sums of many entries do not drift away from the real total.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HumanDuration {
+    seconds: i64,
+}
+
+impl HumanDuration {
+    pub fn zero() -> HumanDuration {
+        HumanDuration { seconds: 0 }
+    }
+
+    /// Duration of a whole number of minutes
+    pub fn from_minutes(minutes: i64) -> HumanDuration {
+        HumanDuration { seconds: minutes * 60 }
+    }
+
+    /// Total number of full minutes
+    pub fn num_minutes(&self) -> i64 {
+        self.seconds / 60
+    }
+
+    /// Number of full hours
+    pub fn hours(&self) -> i64 {
+        self.num_minutes() / 60
+    }
+
+    /// Minutes exceeding the full hours
+    pub fn minutes(&self) -> i64 {
+        self.num_minutes() % 60
+    }
+
+    /// Duration in hours as a decimal number (e.g. `7.75` for 7 hours and 45 minutes)
+    pub fn decimal_hours(&self) -> f64 {
+        self.seconds as f64 / 3600.0
+    }
+
+    /// Serialize as the total number of minutes (for machine-readable output)
+    pub(crate) fn serialize_minutes<S: Serializer>(duration: &HumanDuration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_minutes())
+    }
+}
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}h", self.hours(), self.minutes())
+    }
+}
+
+impl Add for HumanDuration {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        HumanDuration { seconds: self.seconds + other.seconds }
+    }
+}
+
+impl Sub for HumanDuration {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        HumanDuration { seconds: self.seconds - other.seconds }
+    }
+}
+
+impl Sum for HumanDuration {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(HumanDuration::zero(), |a, b| a + b)
+    }
+}
+
+impl From<chrono::Duration> for HumanDuration {
+    fn from(duration: chrono::Duration) -> Self {
+        HumanDuration { seconds: duration.num_seconds() }
+    }
+}
@@ -0,0 +1,436 @@
+use chrono::offset::Local;
+use chrono::{DateTime, NaiveDateTime, NaiveDate, NaiveTime, Utc};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::sync::atomic::{AtomicI64, Ordering};
+use tabled::Tabled;
+
+use crate::duration::HumanDuration;
+use crate::time::{local_datetime, now};
+
+/// Record of a started clock
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ClockinTimestamp {
+    pub start_time: DateTime<Local>,
+
+    /// Comment of the resulting entry (a comment given at clockout is appended)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+
+    /// Tags to add to the resulting entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Project of the resulting entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+
+    /// Task within the project of the resulting entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+
+    /// Finished pauses of the clock
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breaks: Vec<Break>,
+
+    /// Start of the current pause, if the clock is paused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused_since: Option<DateTime<Local>>,
+}
+
+/// Interruption of the work during an entry
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Break {
+    pub start_time: DateTime<Local>,
+    pub end_time: DateTime<Local>,
+}
+
+/// Clock entry
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Entry {
+    /// Time the clock was started
+    pub start_time: DateTime<Local>,
+
+    /// Time the clock ended
+    pub end_time: DateTime<Local>,
+
+    /// Optional comment
+    pub comment: Option<String>,
+
+    /// Tags to categorize the entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Project the entry belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+
+    /// Task within the project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+
+    /// Breaks that do not count towards the duration
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breaks: Vec<Break>,
+
+    /// Identifier that stays the same when the entry is changed (see [Entry::id])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+}
+
+/// Number of characters of an ID shown to the user
+pub const SHORT_ID_LENGTH: usize = 7;
+
+/// Entry formatted for displaying in human-readable form
+///
+/// When serialized, the table-only columns are replaced with full timestamps and durations in minutes.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Tabled, Serialize)]
+pub struct TableEntry {
+    #[tabled(display_with = "display_short_id")]
+    pub id: String,
+
+    #[serde(skip)]
+    pub date: NaiveDate,
+
+    #[serde(skip)]
+    pub from: NaiveTime,
+
+    #[serde(skip)]
+    pub to: NaiveTime,
+
+    #[tabled(skip)]
+    pub start_time: DateTime<Local>,
+
+    #[tabled(skip)]
+    pub end_time: DateTime<Local>,
+
+    #[serde(rename = "duration_minutes", serialize_with = "HumanDuration::serialize_minutes")]
+    pub duration: HumanDuration,
+
+    #[tabled(display_with("display_project", args))]
+    pub project: Option<String>,
+
+    #[tabled(skip)]
+    pub task: Option<String>,
+
+    #[tabled(display_with = "display_tags")]
+    pub tags: Vec<String>,
+
+    #[tabled(display_with = "display_option")]
+    pub comment: Option<String>,
+}
+
+impl ClockinTimestamp {
+    /// Creates a [ClockinTimestamp] referencing the date and time of the function call
+    pub fn now() -> Self {
+        Self::at(now())
+    }
+
+    /// Creates a [ClockinTimestamp] referencing a certain starting time
+    ///
+    /// Fails if the time does not exist or is ambiguous in the local timezone.
+    pub fn starting(time: &NaiveDateTime) -> Result<Self, String> {
+        Ok(Self::at(local_datetime(time)?))
+    }
+
+    fn at(start_time: DateTime<Local>) -> Self {
+        ClockinTimestamp {
+            start_time,
+            comment: None,
+            tags: Vec::new(),
+            project: None,
+            task: None,
+            breaks: Vec::new(),
+            paused_since: None,
+        }
+    }
+
+    /// Set the comment of the resulting entry
+    pub fn with_comment(mut self, comment: Option<String>) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    /// Add tags to the resulting entry
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.add_tags(tags);
+        self
+    }
+
+    /// Add tags to the resulting entry, skipping the ones already present
+    pub fn add_tags(&mut self, tags: Vec<String>) {
+        merge_tags(&mut self.tags, tags);
+    }
+
+    /// Set the project of the resulting entry
+    pub fn with_project(mut self, project: Option<String>) -> Self {
+        self.project = project;
+        self
+    }
+
+    /// Set the task of the resulting entry
+    pub fn with_task(mut self, task: Option<String>) -> Self {
+        self.task = task;
+        self
+    }
+
+    /// Time elapsed since the clock was started
+    pub fn elapsed(&self) -> HumanDuration {
+        (now() - self.start_time).into()
+    }
+
+    /// Time elapsed since the clock was started, without breaks
+    pub fn net_elapsed(&self) -> HumanDuration {
+        let current_break = self.paused_since.map(|p| (now() - p).into()).unwrap_or_default();
+        self.elapsed() - break_duration(&self.breaks) - current_break
+    }
+
+    /// Pause the clock at `time`
+    pub fn pause(&mut self, time: DateTime<Local>) -> Result<(), String> {
+        if let Some(paused_since) = self.paused_since {
+            return Err(format!("Clock is already paused since {}", paused_since));
+        }
+        self.paused_since = Some(time);
+        Ok(())
+    }
+
+    /// Resume the paused clock at `time`, recording the break
+    pub fn resume(&mut self, time: DateTime<Local>) -> Result<(), String> {
+        let start_time = self.paused_since.take()
+            .ok_or("Clock is not paused".to_owned())?;
+        self.breaks.push(Break { start_time, end_time: time });
+        Ok(())
+    }
+}
+
+impl Entry {
+    /// Create a new clock entry
+    ///
+    /// Hashtags in the comment (like `#meeting`) are added to the tags.
+    pub fn create(start_time: DateTime<Local>, end_time: DateTime<Local>, comment: Option<String>, tags: Vec<String>) -> Self {
+        let mut all_tags = Vec::new();
+        merge_tags(&mut all_tags, tags);
+        if let Some(comment) = &comment {
+            merge_tags(&mut all_tags, hashtags(comment));
+        }
+
+        Entry {
+            start_time, end_time, comment,
+            tags: all_tags,
+            project: None,
+            task: None,
+            breaks: Vec::new(),
+            id: None,
+        }.with_new_id()
+    }
+
+    /// Set the project of the entry
+    pub fn with_project(mut self, project: Option<String>) -> Self {
+        self.project = project;
+        self
+    }
+
+    /// Set the task of the entry
+    pub fn with_task(mut self, task: Option<String>) -> Self {
+        self.task = task;
+        self
+    }
+
+    /// Identifier of the entry
+    ///
+    /// It is generated when the entry is created and stored with it, so it does not change with
+    /// the entry. Entries from before IDs were introduced derive theirs from their timespan.
+    pub fn id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| span_id(&self.start_time, &self.end_time))
+    }
+
+    /// Store the current ID, so it is kept when the timespan changes
+    pub fn with_fixed_id(mut self) -> Self {
+        self.id = Some(self.id());
+        self
+    }
+
+    /// Abbreviated ID for display
+    pub fn short_id(&self) -> String {
+        self.id().chars().take(SHORT_ID_LENGTH).collect()
+    }
+
+    /// Generate a new ID (e.g. for a copy of the entry)
+    pub fn with_new_id(mut self) -> Self {
+        self.id = Some(unique_id(&self.start_time, &self.end_time));
+        self
+    }
+
+    /// Set the breaks of the entry
+    pub fn with_breaks(mut self, breaks: Vec<Break>) -> Self {
+        self.breaks = breaks;
+        self
+    }
+
+    /// Duration of the entry without its breaks
+    pub fn duration(&self) -> HumanDuration {
+        HumanDuration::from(self.end_time - self.start_time) - break_duration(&self.breaks)
+    }
+
+    /// Check whether the comment matches `filter` (entries without comment always match)
+    pub fn matches(&self, filter: &Regex) -> bool {
+        self.comment.as_ref().map_or(true, |c| filter.is_match(c))
+    }
+
+    /// Check whether the entry is tagged with `tag`
+    ///
+    /// Hashtags in the comment count as well, so entries from before tags were introduced are found too.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+            || self.comment.as_ref().is_some_and(|c| hashtags(c).iter().any(|t| t == tag))
+    }
+
+    /// Check if the timespan of this entry overlaps with another one
+    ///
+    /// Entries with the same timespan always overlap, so an entry can not be added twice.
+    pub fn overlaps(&self, other: &Entry) -> bool {
+        self.same_span(other)
+            || (self.start_time > other.start_time && self.start_time < other.end_time)
+            || (self.end_time > other.start_time && self.end_time < other.end_time)
+            || (other.start_time > self.start_time && other.start_time < self.end_time)
+            || (other.end_time > self.start_time && other.end_time < self.end_time)
+    }
+
+    /// Check whether both entries start and end at the same time
+    pub fn same_span(&self, other: &Entry) -> bool {
+        self.start_time == other.start_time && self.end_time == other.end_time
+    }
+}
+
+impl From<&Entry> for TableEntry {
+    fn from(entry: &Entry) -> Self {
+        TableEntry {
+            id: entry.id(),
+            date: entry.start_time.date_naive(),
+            from: entry.start_time.time(),
+            to: entry.end_time.time(),
+            start_time: entry.start_time,
+            end_time: entry.end_time,
+            duration: entry.duration(),
+            project: entry.project.clone(),
+            task: entry.task.clone(),
+            tags: entry.tags.clone(),
+            comment: entry.comment.clone(),
+        }
+    }
+}
+
+fn display_short_id(id: &str) -> String {
+    id.chars().take(SHORT_ID_LENGTH).collect()
+}
+
+fn display_project(entry: &TableEntry) -> String {
+    match (&entry.project, &entry.task) {
+        (Some(project), Some(task)) => format!("{} / {}", project, task),
+        (project, task) => project.clone().or(task.clone()).unwrap_or_default(),
+    }
+}
+
+fn display_tags(tags: &[String]) -> String {
+    tags.join(", ")
+}
+
+fn display_option(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+/// Identifier derived from a timespan (64 bit FNV-1a hash, which is stable across versions)
+fn span_id(start_time: &DateTime<Local>, end_time: &DateTime<Local>) -> String {
+    format!("{:016x}", fnv_hash(&[start_time.timestamp(), end_time.timestamp()]))
+}
+
+/// Identifier for a new entry, which differs from the ones of all entries with the same timespan
+///
+/// Besides the timespan, the current time and a counter of the IDs generated by this process are hashed.
+fn unique_id(start_time: &DateTime<Local>, end_time: &DateTime<Local>) -> String {
+    static GENERATED: AtomicI64 = AtomicI64::new(0);
+    let created = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    let count = GENERATED.fetch_add(1, Ordering::Relaxed);
+    format!("{:016x}", fnv_hash(&[start_time.timestamp(), end_time.timestamp(), created, count]))
+}
+
+/// 64 bit FNV-1a hash of `values`
+fn fnv_hash(values: &[i64]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in values.iter().flat_map(|v| v.to_le_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Total duration of `breaks`
+fn break_duration(breaks: &[Break]) -> HumanDuration {
+    breaks.iter()
+        .map(|b| (b.end_time - b.start_time).into())
+        .sum()
+}
+
+/// Extract all `#hashtags` from a text
+pub(crate) fn hashtags(text: &str) -> Vec<String> {
+    let re = Regex::new(r"(?:^|\s)#(\w[\w\-]*)").unwrap();
+    re.captures_iter(text)
+        .map(|c| c[1].to_owned())
+        .collect()
+}
+
+/// Add tags that are not yet present, preserving their order
+pub(crate) fn merge_tags(tags: &mut Vec<String>, new_tags: Vec<String>) {
+    for tag in new_tags {
+        let tag = tag.trim_start_matches('#').to_owned();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::at;
+
+    #[test]
+    fn overlapping_spans() {
+        let entry = Entry::create(at("2024-01-15 09:00"), at("2024-01-15 12:00"), None, Vec::new());
+        let overlaps = |from: &str, to: &str| entry.overlaps(&Entry::create(at(from), at(to), None, Vec::new()));
+        assert!(overlaps("2024-01-15 09:00", "2024-01-15 12:00"));
+        assert!(overlaps("2024-01-15 08:00", "2024-01-15 10:00"));
+        assert!(overlaps("2024-01-15 10:00", "2024-01-15 11:00"));
+        assert!(overlaps("2024-01-15 08:00", "2024-01-15 13:00"));
+        assert!(!overlaps("2024-01-15 12:00", "2024-01-15 13:00"));
+        assert!(!overlaps("2024-01-15 07:00", "2024-01-15 09:00"));
+    }
+
+    #[test]
+    fn entries_with_the_same_span_get_different_ids() {
+        let first = Entry::create(at("2024-01-15 09:00"), at("2024-01-15 12:00"), None, Vec::new());
+        let second = Entry::create(at("2024-01-15 09:00"), at("2024-01-15 12:00"), None, Vec::new());
+        assert_ne!(first.id(), second.id());
+        assert_ne!(first.id(), second.clone().with_new_id().id());
+    }
+
+    #[test]
+    fn id_is_kept_when_the_span_changes() {
+        let mut entry = Entry::create(at("2024-01-15 09:00"), at("2024-01-15 12:00"), None, Vec::new());
+        let id = entry.id();
+        entry.start_time = at("2024-01-16 09:00");
+        entry.end_time = at("2024-01-16 12:00");
+        assert_eq!(entry.id(), id);
+        assert_eq!(entry.short_id().len(), SHORT_ID_LENGTH);
+    }
+
+    #[test]
+    fn entries_without_stored_id_derive_it_from_their_span() {
+        let yaml = "start_time: 2024-01-15T09:00:00Z\nend_time: 2024-01-15T12:00:00Z\ncomment: null\n";
+        let entry: Entry = serde_yaml::from_str(yaml).unwrap();
+        let again: Entry = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(entry.id(), again.id());
+        assert_eq!(entry.id(), span_id(&entry.start_time, &entry.end_time));
+        assert_eq!(entry.clone().with_fixed_id().id(), entry.id());
+    }
+}
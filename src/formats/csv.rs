@@ -0,0 +1,215 @@
+//! Spreadsheet-friendly CSV with one entry per row
+//!
+//! Columns are `date`, `start`, `end`, `duration`, `breaks`, `project`, `task`, `tags` and `comment`.
+//! On import the columns are matched by their header, so they may be in any order and only
+//! `date`, `start` and either `end` or `duration` are required.
+//! The end is exported with its date, but may be given as a time alone.
+//! Breaks are separated by commas, each like `2024-01-15 12:00-12:30`.
+
+use crate::entry::{Break, Entry};
+use crate::time::{local_datetime, parse_absolute, parse_clock_time, parse_date, parse_hours, parse_time_range};
+use super::ParsedRow;
+
+const HEADERS: [&str; 9] = ["date", "start", "end", "duration", "breaks", "project", "task", "tags", "comment"];
+
+/// Serialize entries to CSV
+pub fn export(entries: &[Entry], delimiter: u8) -> Result<String, String> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(Vec::new());
+
+    writer.write_record(HEADERS)
+        .map_err(|e| e.to_string())?;
+    for entry in entries {
+        let duration = entry.duration();
+        writer.write_record([
+            entry.start_time.format("%Y-%m-%d").to_string(),
+            entry.start_time.format("%H:%M").to_string(),
+            entry.end_time.format("%Y-%m-%d %H:%M").to_string(),
+            format!("{:02}:{:02}", duration.hours(), duration.minutes()),
+            entry.breaks.iter()
+                .map(|b| format!("{}-{}", b.start_time.format("%Y-%m-%d %H:%M"), b.end_time.format("%H:%M")))
+                .collect::<Vec<_>>()
+                .join(", "),
+            entry.project.clone().unwrap_or_default(),
+            entry.task.clone().unwrap_or_default(),
+            entry.tags.join(" "),
+            entry.comment.clone().unwrap_or_default(),
+        ]).map_err(|e| e.to_string())?;
+    }
+
+    let content = writer.into_inner()
+        .map_err(|e| e.to_string())?;
+    String::from_utf8(content)
+        .map_err(|e| e.to_string())
+}
+
+/// Parse entries from CSV
+///
+/// Returns the line number and the parsed entry (or the reason it could not be parsed) for each row.
+pub fn parse(content: &str, delimiter: u8) -> Result<Vec<ParsedRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader.headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let date_col = column("date").ok_or("Missing column 'date'".to_owned())?;
+    let start_col = column("start").ok_or("Missing column 'start'".to_owned())?;
+    let end_col = column("end");
+    let duration_col = column("duration");
+    let breaks_col = column("breaks");
+    if end_col.is_none() && duration_col.is_none() {
+        return Err("Missing column 'end' or 'duration'".to_owned());
+    }
+    let project_col = column("project");
+    let task_col = column("task");
+    let tags_col = column("tags");
+    let comment_col = column("comment");
+
+    let rows = reader.records()
+        .map(|record| {
+            let record = record.map_err(|e| (e.position().map(|p| p.line() as usize).unwrap_or(0), e.to_string()))?;
+            let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+            let field = |col: Option<usize>| col.and_then(|c| record.get(c))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+                .map(|f| f.to_owned());
+
+            let parse_row = || -> Result<Entry, String> {
+                let date = field(Some(date_col)).ok_or("empty date")?;
+                let date = parse_date(&date).ok_or(format!("unable to parse date '{}'", date))?;
+                let start_time = field(Some(start_col)).ok_or("empty start time")?;
+                let start_time = parse_clock_time(&start_time).ok_or(format!("unable to parse time '{}'", start_time))?;
+                let start = local_datetime(&date.and_time(start_time))?;
+
+                let end = match (field(end_col), field(duration_col)) {
+                    (Some(end), _) => match (parse_absolute(&end), parse_clock_time(&end)) {
+                        (Some(end), _) => local_datetime(&end)?,
+                        // entries ending before they started continue past midnight
+                        (None, Some(end_time)) if end_time < start_time => {
+                            let end_date = date.succ_opt().ok_or("date out of range")?;
+                            local_datetime(&end_date.and_time(end_time))?
+                        },
+                        (None, Some(end_time)) => local_datetime(&date.and_time(end_time))?,
+                        (None, None) => return Err(format!("unable to parse end '{}'", end)),
+                    },
+                    (None, Some(duration)) => start.checked_add_signed(parse_hours(&duration)?)
+                        .ok_or(format!("duration '{}' is too long", duration))?,
+                    (None, None) => return Err("neither end time nor duration given".to_owned()),
+                };
+                let breaks = field(breaks_col)
+                    .map(|b| b.split(',').map(|b| parse_break(b.trim())).collect::<Result<Vec<_>, _>>())
+                    .transpose()?
+                    .unwrap_or_default();
+
+                let tags = field(tags_col)
+                    .map(|t| t.split_whitespace().map(|t| t.to_owned()).collect())
+                    .unwrap_or_default();
+                Ok(Entry::create(start, end, field(comment_col), tags)
+                    .with_project(field(project_col))
+                    .with_task(field(task_col))
+                    .with_breaks(breaks))
+            };
+
+            Ok((line, parse_row()))
+        })
+        .map(|row| row.unwrap_or_else(|(line, e)| (line, Err(e))))
+        .collect();
+
+    Ok(rows)
+}
+
+/// Parse a break like `2024-01-15 12:00-12:30`
+fn parse_break(s: &str) -> Result<Break, String> {
+    let (start, end) = parse_time_range(s)
+        .map_err(|_| format!("unable to parse break '{}'", s))?;
+    Ok(Break { start_time: local_datetime(&start)?, end_time: local_datetime(&end)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::at;
+
+    fn parse_entries(content: &str) -> Vec<Result<Entry, String>> {
+        parse(content, b',').unwrap().into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    #[test]
+    fn exported_entries_are_imported_again() {
+        let entries = vec![
+            Entry::create(at("2024-01-15 09:00"), at("2024-01-15 12:30"), Some("Review, part 1".to_owned()), vec!["a".to_owned(), "b".to_owned()])
+                .with_project(Some("foliot".to_owned()))
+                .with_task(Some("import".to_owned())),
+            Entry::create(at("2024-01-15 22:00"), at("2024-01-16 06:00"), None, Vec::new())
+                .with_breaks(vec![
+                    Break { start_time: at("2024-01-15 23:30"), end_time: at("2024-01-16 00:15") },
+                    Break { start_time: at("2024-01-16 03:00"), end_time: at("2024-01-16 03:10") },
+                ]),
+            Entry::create(at("2024-01-17 09:00"), at("2024-01-19 17:00"), None, Vec::new()),
+        ];
+        let imported: Vec<Entry> = parse_entries(&export(&entries, b',').unwrap()).into_iter()
+            .map(|e| e.unwrap())
+            .collect();
+
+        assert_eq!(imported.len(), entries.len());
+        for (imported, entry) in imported.iter().zip(&entries) {
+            assert!(imported.same_span(entry), "{:?}", imported);
+            assert_eq!(imported.breaks, entry.breaks);
+            assert_eq!(imported.comment, entry.comment);
+            assert_eq!(imported.tags, entry.tags);
+            assert_eq!(imported.project, entry.project);
+            assert_eq!(imported.task, entry.task);
+        }
+    }
+
+    #[test]
+    fn end_or_duration_of_timesheets() {
+        let entries = parse_entries("Date;Start;End;Duration\n\
+                                     15.01.2024;09:00;12:00;\n\
+                                     2024-01-15;22:00;01:00;\n\
+                                     2024-01-16;09:00;;1.5\n\
+                                     2024-01-16;13:00;;2:15\n"
+            .replace(';', ",").as_str());
+        let spans: Vec<_> = entries.into_iter().map(|e| e.map(|e| (e.start_time, e.end_time))).collect();
+
+        assert_eq!(spans, vec![
+            Ok((at("2024-01-15 09:00"), at("2024-01-15 12:00"))),
+            Ok((at("2024-01-15 22:00"), at("2024-01-16 01:00"))),
+            Ok((at("2024-01-16 09:00"), at("2024-01-16 10:30"))),
+            Ok((at("2024-01-16 13:00"), at("2024-01-16 15:15"))),
+        ]);
+    }
+
+    #[test]
+    fn bad_rows_are_rejected() {
+        let entries = parse_entries("date,start,end,duration,breaks\n\
+                                     2024-01-15,09:00,,1e30,\n\
+                                     2024-01-15,09:00,,999999999999:00,\n\
+                                     2024-01-15,09:00,,-2:00,\n\
+                                     2024-01-15,09:00,,1:75,\n\
+                                     2024-01-15,09:00,,inf,\n\
+                                     2024-13-15,09:00,10:00,,\n\
+                                     2024-01-15,9 o'clock,10:00,,\n\
+                                     2024-01-15,09:00,later,,\n\
+                                     2024-01-15,09:00,,,\n\
+                                     2024-01-15,09:00,10:00,,lunch\n");
+        for (i, entry) in entries.iter().enumerate() {
+            assert!(entry.is_err(), "row {}: {:?}", i + 1, entry);
+        }
+        assert_eq!(entries.len(), 10);
+    }
+
+    #[test]
+    fn required_columns() {
+        assert!(parse("start,end\n09:00,10:00\n", b',').is_err());
+        assert!(parse("date,end\n2024-01-15,10:00\n", b',').is_err());
+        assert!(parse("date,start,comment\n2024-01-15,09:00,x\n", b',').is_err());
+    }
+}
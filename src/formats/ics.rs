@@ -0,0 +1,105 @@
+//! iCalendar (RFC 5545) with one event per entry
+
+use chrono::offset::Utc;
+use chrono::{DateTime, TimeZone};
+
+use crate::entry::Entry;
+use crate::time::now;
+
+/// Maximum length of a content line in octets before it has to be folded
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Serialize entries of a namespace to an iCalendar file
+///
+/// The UID of each event is derived from the namespace and the ID of the entry, so a calendar
+/// subscribed to the exported file recognizes entries across exports, even if they were amended.
+pub fn export(entries: &[Entry], namespace: &str) -> String {
+    let stamp = format_time(&now());
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        format!("PRODID:-//foliot//foliot {}//EN", env!("CARGO_PKG_VERSION")),
+        "CALSCALE:GREGORIAN".to_owned(),
+        format!("X-WR-CALNAME:{}", escape(namespace)),
+    ];
+
+    for entry in entries {
+        let summary = entry.comment.as_ref()
+            .or(entry.project.as_ref())
+            .map(|s| s.as_str())
+            .unwrap_or(namespace);
+
+        lines.push("BEGIN:VEVENT".to_owned());
+        lines.push(format!("UID:{}@{}.foliot", entry.id(), escape(namespace)));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART:{}", format_time(&entry.start_time)));
+        lines.push(format!("DTEND:{}", format_time(&entry.end_time)));
+        lines.push(format!("SUMMARY:{}", escape(summary)));
+        lines.push(format!("CATEGORIES:{}", escape(namespace)));
+
+        let mut description = Vec::new();
+        if let Some(project) = &entry.project {
+            description.push(format!("Project: {}", project));
+        }
+        if let Some(task) = &entry.task {
+            description.push(format!("Task: {}", task));
+        }
+        if !entry.tags.is_empty() {
+            description.push(format!("Tags: {}", entry.tags.join(", ")));
+        }
+        if !description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+        }
+        lines.push("END:VEVENT".to_owned());
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines.iter()
+        .map(|l| fold(l) + "\r\n")
+        .collect()
+}
+
+/// Format a point in time in UTC
+fn format_time<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+    time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape special characters in text values
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Split long lines into multiple ones, each continuation starting with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::at;
+
+    #[test]
+    fn events_keep_their_uid_when_the_entry_is_amended() {
+        let entry = Entry::create(at("2024-01-15 09:00"), at("2024-01-15 10:00"), None, Vec::new());
+        let mut amended = entry.clone();
+        amended.end_time = at("2024-01-15 11:00");
+
+        let uid = format!("UID:{}@default.foliot\r\n", entry.id());
+        assert!(export(&[entry], "default").contains(&uid));
+        assert!(export(&[amended], "default").contains(&uid));
+    }
+}
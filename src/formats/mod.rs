@@ -0,0 +1,12 @@
+//! Conversion of entries from and to other file formats
+
+pub mod csv;
+pub mod ics;
+pub mod timewarrior;
+pub mod toggl;
+pub mod watson;
+
+use crate::entry::Entry;
+
+/// Line number of an imported record and the entry parsed from it (or the reason it was rejected)
+pub type ParsedRow = (usize, Result<Entry, String>);
@@ -0,0 +1,85 @@
+//! JSON as written by `timew export`
+//!
+//! Each interval has a `start` and `end` in UTC (e.g. `20231001T080000Z`), optional `tags` and
+//! an optional `annotation`, which becomes the comment.
+
+use chrono::offset::{Local, Utc};
+use chrono::{DateTime, NaiveDateTime, TimeZone};
+use serde::Deserialize;
+
+use crate::entry::Entry;
+use super::ParsedRow;
+
+#[derive(Deserialize)]
+struct Interval {
+    start: String,
+    end: Option<String>,
+
+    #[serde(default)]
+    tags: Vec<String>,
+
+    annotation: Option<String>,
+}
+
+/// Parse entries from a Timewarrior export
+pub fn parse(content: &str) -> Result<Vec<ParsedRow>, String> {
+    let intervals: Vec<serde_json::Value> = serde_json::from_str(content)
+        .map_err(|e| e.to_string())?;
+
+    let rows = intervals.into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let parse_interval = || -> Result<Entry, String> {
+                let interval: Interval = serde_json::from_value(value)
+                    .map_err(|e| e.to_string())?;
+                let end = interval.end.ok_or("interval is still open".to_owned())?;
+                Ok(Entry::create(parse_time(&interval.start)?, parse_time(&end)?, interval.annotation, interval.tags))
+            };
+            (i + 1, parse_interval())
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+fn parse_time(s: &str) -> Result<DateTime<Local>, String> {
+    NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%SZ")
+        .map(|t| Utc.from_utc_datetime(&t).with_timezone(&Local))
+        .map_err(|_| format!("unable to parse time '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn intervals() {
+        let rows = parse(r#"[
+            {"id": 2, "start": "20240115T080000Z", "end": "20240115T093000Z", "tags": ["foliot", "review"], "annotation": "Reading docs"},
+            {"id": 1, "start": "20240115T100000Z", "end": "20240115T110000Z"}
+        ]"#).unwrap();
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap().with_timezone(&Local);
+        let first = rows[0].1.as_ref().unwrap();
+        assert_eq!((first.start_time, first.end_time), (start, start + Duration::minutes(90)));
+        assert_eq!(first.tags, vec!["foliot", "review"]);
+        assert_eq!(first.comment.as_deref(), Some("Reading docs"));
+        let second = rows[1].1.as_ref().unwrap();
+        assert_eq!((second.start_time, second.end_time), (start + Duration::hours(2), start + Duration::hours(3)));
+        assert!(second.tags.is_empty());
+    }
+
+    #[test]
+    fn bad_intervals_are_rejected() {
+        let rows = parse(r#"[
+            {"start": "20240115T080000Z"},
+            {"start": "2024-01-15 08:00", "end": "20240115T093000Z"},
+            {"end": "20240115T093000Z"},
+            "20240115T080000Z"
+        ]"#).unwrap();
+        assert_eq!(rows.iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert!(rows.iter().all(|(_, entry)| entry.is_err()), "{:?}", rows);
+        assert!(parse("{}").is_err());
+    }
+}
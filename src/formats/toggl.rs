@@ -0,0 +1,110 @@
+//! CSV of Toggl Track's detailed report
+//!
+//! The relevant columns are `Start date`, `Start time`, `End date`, `End time`, `Project`,
+//! `Task`, `Description` (which becomes the comment) and `Tags` (comma separated).
+
+use chrono::offset::Local;
+use chrono::DateTime;
+
+use crate::entry::Entry;
+use crate::time::{local_datetime, parse_clock_time, parse_date};
+use super::ParsedRow;
+
+/// Parse entries from a Toggl CSV export
+pub fn parse(content: &str) -> Result<Vec<ParsedRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader.headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name)
+        .ok_or(format!("Missing column '{}'", name));
+    let start_date_col = column("start date")?;
+    let start_time_col = column("start time")?;
+    let end_date_col = column("end date")?;
+    let end_time_col = column("end time")?;
+    let project_col = column("project").ok();
+    let task_col = column("task").ok();
+    let description_col = column("description").ok();
+    let tags_col = column("tags").ok();
+
+    let rows = reader.records()
+        .enumerate()
+        .map(|(i, record)| {
+            let parse_row = || -> Result<Entry, String> {
+                let record = record.map_err(|e| e.to_string())?;
+                let field = |col: Option<usize>| col.and_then(|c| record.get(c))
+                    .map(|f| f.trim())
+                    .filter(|f| !f.is_empty())
+                    .map(|f| f.to_owned());
+
+                let start = datetime(&field(Some(start_date_col)).unwrap_or_default(),
+                                     &field(Some(start_time_col)).unwrap_or_default())?;
+                let end = datetime(&field(Some(end_date_col)).unwrap_or_default(),
+                                   &field(Some(end_time_col)).unwrap_or_default())?;
+                let tags = field(tags_col)
+                    .map(|t| t.split(',').map(|t| t.trim().to_owned()).collect())
+                    .unwrap_or_default();
+
+                Ok(Entry::create(start, end, field(description_col), tags)
+                    .with_project(field(project_col))
+                    .with_task(field(task_col)))
+            };
+            // the header is on the first line
+            (i + 2, parse_row())
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+fn datetime(date: &str, time: &str) -> Result<DateTime<Local>, String> {
+    let date = parse_date(date)
+        .ok_or(format!("unable to parse date '{}'", date))?;
+    let time = parse_clock_time(time)
+        .ok_or(format!("unable to parse time '{}'", time))?;
+    local_datetime(&date.and_time(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::at;
+
+    #[test]
+    fn detailed_report() {
+        let rows = parse("User,Email,Project,Task,Description,Start date,Start time,End date,End time,Duration,Tags\n\
+                          Kim,kim@example.com,foliot,import,Reading docs,2024-01-15,09:00:00,2024-01-15,10:30:00,01:30:00,\"docs, review\"\n\
+                          Kim,kim@example.com,,,,2024-01-15,23:00:00,2024-01-16,01:00:00,02:00:00,\n").unwrap();
+
+        assert_eq!(rows.iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![2, 3]);
+        let first = rows[0].1.as_ref().unwrap();
+        assert_eq!((first.start_time, first.end_time), (at("2024-01-15 09:00"), at("2024-01-15 10:30")));
+        assert_eq!(first.comment.as_deref(), Some("Reading docs"));
+        assert_eq!(first.tags, vec!["docs", "review"]);
+        assert_eq!(first.project.as_deref(), Some("foliot"));
+        assert_eq!(first.task.as_deref(), Some("import"));
+        let second = rows[1].1.as_ref().unwrap();
+        assert_eq!((second.start_time, second.end_time), (at("2024-01-15 23:00"), at("2024-01-16 01:00")));
+        assert_eq!((second.comment.as_ref(), second.project.as_ref()), (None, None));
+    }
+
+    #[test]
+    fn bad_rows_are_rejected() {
+        let rows = parse("Start date,Start time,End date,End time\n\
+                          2024-01-15,09:00:00,,10:00:00\n\
+                          15/01/2024,09:00:00,15/01/2024,10:00:00\n\
+                          2024-01-15,25:00:00,2024-01-15,26:00:00\n").unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|(_, entry)| entry.is_err()), "{:?}", rows);
+    }
+
+    #[test]
+    fn required_columns() {
+        assert!(parse("Start date,Start time,End date\n2024-01-15,09:00:00,2024-01-15\n").is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! Watson's JSON output (`watson log --json`) or its raw `frames` file
+//!
+//! Watson's projects map to projects, its tags to tags.
+
+use chrono::offset::Local;
+use chrono::{DateTime, TimeZone};
+use serde::Deserialize;
+
+use crate::entry::Entry;
+use super::ParsedRow;
+
+/// Frame as printed by `watson log --json`
+#[derive(Deserialize)]
+struct LogFrame {
+    project: String,
+    start: String,
+    stop: String,
+
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Parse entries from a Watson export or frames file
+pub fn parse(content: &str) -> Result<Vec<ParsedRow>, String> {
+    let frames: Vec<serde_json::Value> = serde_json::from_str(content)
+        .map_err(|e| e.to_string())?;
+
+    let rows = frames.into_iter()
+        .enumerate()
+        .map(|(i, value)| (i + 1, parse_frame(value)))
+        .collect();
+
+    Ok(rows)
+}
+
+fn parse_frame(value: serde_json::Value) -> Result<Entry, String> {
+    let (start, end, project, tags) = if value.is_array() {
+        // frames file: `[start, stop, project, id, tags, updated_at]`
+        let frame: Vec<serde_json::Value> = serde_json::from_value(value)
+            .map_err(|e| e.to_string())?;
+        let field = |i: usize| frame.get(i).cloned().unwrap_or_default();
+        let start = field(0).as_i64().ok_or("missing start of frame".to_owned())?;
+        let stop = field(1).as_i64().ok_or("missing stop of frame".to_owned())?;
+        let project = field(2).as_str().ok_or("missing project of frame".to_owned())?.to_owned();
+        let tags = serde_json::from_value(field(4)).unwrap_or_default();
+        (timestamp(start)?, timestamp(stop)?, project, tags)
+    } else {
+        let frame: LogFrame = serde_json::from_value(value)
+            .map_err(|e| e.to_string())?;
+        (rfc3339(&frame.start)?, rfc3339(&frame.stop)?, frame.project, frame.tags)
+    };
+
+    Ok(Entry::create(start, end, None, tags)
+        .with_project(Some(project)))
+}
+
+fn timestamp(seconds: i64) -> Result<DateTime<Local>, String> {
+    Local.timestamp_opt(seconds, 0).single()
+        .ok_or(format!("invalid timestamp '{}'", seconds))
+}
+
+fn rfc3339(s: &str) -> Result<DateTime<Local>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Local))
+        .map_err(|_| format!("unable to parse time '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn log_output() {
+        let rows = parse(r#"[
+            {"id": "a1", "project": "foliot", "start": "2024-01-15T09:00:00+01:00", "stop": "2024-01-15T10:30:00+01:00", "tags": ["review"]}
+        ]"#).unwrap();
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap().with_timezone(&Local);
+        let entry = rows[0].1.as_ref().unwrap();
+        assert_eq!((entry.start_time, entry.end_time), (start, start + Duration::minutes(90)));
+        assert_eq!(entry.project.as_deref(), Some("foliot"));
+        assert_eq!(entry.tags, vec!["review"]);
+    }
+
+    #[test]
+    fn frames_file() {
+        let rows = parse(r#"[[1705305600, 1705311000, "foliot", "a1", ["review"], 1705311000]]"#).unwrap();
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap().with_timezone(&Local);
+        let entry = rows[0].1.as_ref().unwrap();
+        assert_eq!((entry.start_time, entry.end_time), (start, start + Duration::minutes(90)));
+        assert_eq!(entry.project.as_deref(), Some("foliot"));
+        assert_eq!(entry.tags, vec!["review"]);
+    }
+
+    #[test]
+    fn bad_frames_are_rejected() {
+        let rows = parse(r#"[
+            ["1705305600", 1705311000, "foliot"],
+            [1705305600, 1705311000],
+            [1705305600, 999999999999999999, "foliot"],
+            {"project": "foliot", "start": "2024-01-15 09:00", "stop": "2024-01-15T10:30:00+01:00"},
+            {"start": "2024-01-15T09:00:00+01:00", "stop": "2024-01-15T10:30:00+01:00"}
+        ]"#).unwrap();
+        assert_eq!(rows.len(), 5);
+        assert!(rows.iter().all(|(_, entry)| entry.is_err()), "{:?}", rows);
+        assert!(parse("not json").is_err());
+    }
+}
@@ -0,0 +1,389 @@
+//! Offline rules for public holidays of some countries and their regions
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Serialize, Deserialize};
+use std::fmt::Display;
+use std::str::FromStr;
+use tabled::Tabled;
+
+/// Public holidays of a country or one of its regions (e.g. `DE-BY` for Bavaria)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct HolidayCalendar {
+    country: Country,
+    region: Option<String>,
+}
+
+/// A public holiday
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Tabled, Serialize)]
+pub struct Holiday {
+    #[tabled(display_with = "display_date")]
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+/// Countries with built-in holiday rules
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Country {
+    Germany,
+    Austria,
+    Switzerland,
+}
+
+/// Rule for the date of a holiday in a year
+#[derive(Copy, Clone, Debug)]
+enum Day {
+    /// Same month and day every year
+    Fixed(u32, u32),
+
+    /// Number of days after Easter Sunday
+    Easter(i64),
+
+    /// Day of Repentance and Prayer (the Wednesday before November 23rd)
+    Repentance,
+}
+
+/// Regions a holiday applies to
+#[derive(Copy, Clone, Debug)]
+enum Regions {
+    All,
+    Only(&'static [&'static str]),
+    Except(&'static [&'static str]),
+}
+
+/// Holiday of a country
+struct Rule {
+    name: &'static str,
+    day: Day,
+    regions: Regions,
+
+    /// First year the holiday exists
+    since: i32,
+}
+
+const fn rule(name: &'static str, day: Day, regions: Regions) -> Rule {
+    Rule { name, day, regions, since: 0 }
+}
+
+const fn rule_since(name: &'static str, day: Day, regions: Regions, since: i32) -> Rule {
+    Rule { name, day, regions, since }
+}
+
+const GERMAN_STATES: &[&str] = &[
+    "BB", "BE", "BW", "BY", "HB", "HE", "HH", "MV", "NI", "NW", "RP", "SH", "SL", "SN", "ST", "TH",
+];
+
+const GERMANY: &[Rule] = &[
+    rule("Neujahr", Day::Fixed(1, 1), Regions::All),
+    rule("Heilige Drei Könige", Day::Fixed(1, 6), Regions::Only(&["BW", "BY", "ST"])),
+    rule_since("Internationaler Frauentag", Day::Fixed(3, 8), Regions::Only(&["BE"]), 2019),
+    rule_since("Internationaler Frauentag", Day::Fixed(3, 8), Regions::Only(&["MV"]), 2023),
+    rule("Karfreitag", Day::Easter(-2), Regions::All),
+    rule("Ostersonntag", Day::Easter(0), Regions::Only(&["BB"])),
+    rule("Ostermontag", Day::Easter(1), Regions::All),
+    rule("Tag der Arbeit", Day::Fixed(5, 1), Regions::All),
+    rule("Christi Himmelfahrt", Day::Easter(39), Regions::All),
+    rule("Pfingstsonntag", Day::Easter(49), Regions::Only(&["BB"])),
+    rule("Pfingstmontag", Day::Easter(50), Regions::All),
+    rule("Fronleichnam", Day::Easter(60), Regions::Only(&["BW", "BY", "HE", "NW", "RP", "SL"])),
+    rule("Mariä Himmelfahrt", Day::Fixed(8, 15), Regions::Only(&["SL"])),
+    rule_since("Weltkindertag", Day::Fixed(9, 20), Regions::Only(&["TH"]), 2019),
+    rule("Tag der Deutschen Einheit", Day::Fixed(10, 3), Regions::All),
+    rule("Reformationstag", Day::Fixed(10, 31), Regions::Only(&["BB", "MV", "SN", "ST", "TH"])),
+    rule_since("Reformationstag", Day::Fixed(10, 31), Regions::Only(&["HB", "HH", "NI", "SH"]), 2018),
+    rule("Allerheiligen", Day::Fixed(11, 1), Regions::Only(&["BW", "BY", "NW", "RP", "SL"])),
+    rule("Buß- und Bettag", Day::Repentance, Regions::Only(&["SN"])),
+    rule("1. Weihnachtstag", Day::Fixed(12, 25), Regions::All),
+    rule("2. Weihnachtstag", Day::Fixed(12, 26), Regions::All),
+];
+
+const AUSTRIA: &[Rule] = &[
+    rule("Neujahr", Day::Fixed(1, 1), Regions::All),
+    rule("Heilige Drei Könige", Day::Fixed(1, 6), Regions::All),
+    rule("Ostermontag", Day::Easter(1), Regions::All),
+    rule("Staatsfeiertag", Day::Fixed(5, 1), Regions::All),
+    rule("Christi Himmelfahrt", Day::Easter(39), Regions::All),
+    rule("Pfingstmontag", Day::Easter(50), Regions::All),
+    rule("Fronleichnam", Day::Easter(60), Regions::All),
+    rule("Mariä Himmelfahrt", Day::Fixed(8, 15), Regions::All),
+    rule("Nationalfeiertag", Day::Fixed(10, 26), Regions::All),
+    rule("Allerheiligen", Day::Fixed(11, 1), Regions::All),
+    rule("Mariä Empfängnis", Day::Fixed(12, 8), Regions::All),
+    rule("Christtag", Day::Fixed(12, 25), Regions::All),
+    rule("Stefanitag", Day::Fixed(12, 26), Regions::All),
+];
+
+const SWISS_CANTONS: &[&str] = &[
+    "AG", "AI", "AR", "BE", "BL", "BS", "FR", "GE", "GL", "GR", "JU", "LU", "NE",
+    "NW", "OW", "SG", "SH", "SO", "SZ", "TG", "TI", "UR", "VD", "VS", "ZG", "ZH",
+];
+
+/// Cantons with predominantly catholic holidays
+const CATHOLIC_CANTONS: &[&str] = &["AI", "FR", "JU", "LU", "NW", "OW", "SO", "SZ", "TI", "UR", "VS", "ZG"];
+
+const SWITZERLAND: &[Rule] = &[
+    rule("Neujahrstag", Day::Fixed(1, 1), Regions::All),
+    rule("Berchtoldstag", Day::Fixed(1, 2), Regions::Only(&[
+        "AG", "BE", "FR", "GL", "JU", "LU", "NE", "OW", "SH", "SO", "TG", "VD", "ZG", "ZH",
+    ])),
+    rule("Karfreitag", Day::Easter(-2), Regions::Except(&["TI", "VS"])),
+    rule("Ostermontag", Day::Easter(1), Regions::Except(&["VS"])),
+    rule("Tag der Arbeit", Day::Fixed(5, 1), Regions::Only(&["BL", "BS", "JU", "NE", "SH", "TI", "ZH"])),
+    rule("Auffahrt", Day::Easter(39), Regions::All),
+    rule("Pfingstmontag", Day::Easter(50), Regions::Except(&["VS"])),
+    rule("Fronleichnam", Day::Easter(60), Regions::Only(CATHOLIC_CANTONS)),
+    rule("Bundesfeiertag", Day::Fixed(8, 1), Regions::All),
+    rule("Mariä Himmelfahrt", Day::Fixed(8, 15), Regions::Only(CATHOLIC_CANTONS)),
+    rule("Allerheiligen", Day::Fixed(11, 1), Regions::Only(&[
+        "AI", "FR", "GL", "JU", "LU", "NW", "OW", "SG", "SO", "SZ", "TI", "UR", "VS", "ZG",
+    ])),
+    rule("Mariä Empfängnis", Day::Fixed(12, 8), Regions::Only(&[
+        "AI", "FR", "LU", "NW", "OW", "SZ", "TI", "UR", "VS", "ZG",
+    ])),
+    rule("Weihnachtstag", Day::Fixed(12, 25), Regions::All),
+    rule("Stephanstag", Day::Fixed(12, 26), Regions::Except(&["GE", "JU", "NE", "VD", "VS"])),
+    rule("Wiederherstellung der Republik", Day::Fixed(12, 31), Regions::Only(&["GE"])),
+];
+
+impl HolidayCalendar {
+    /// All holidays of `year` sorted by date
+    ///
+    /// Holidays that fall on dates out of the supported range are left out.
+    pub fn holidays(&self, year: i32) -> Vec<Holiday> {
+        let mut holidays: Vec<Holiday> = self.country.rules().iter()
+            .filter(|r| year >= r.since && r.regions.contains(self.region.as_deref()))
+            .filter_map(|r| Some(Holiday { date: r.day.date(year)?, name: r.name.to_owned() }))
+            .collect();
+        holidays.sort();
+        holidays
+    }
+
+    /// The holiday on `date`, if any
+    pub fn holiday(&self, date: NaiveDate) -> Option<Holiday> {
+        self.holidays(date.year()).into_iter()
+            .find(|h| h.date == date)
+    }
+}
+
+impl Country {
+    fn code(&self) -> &'static str {
+        match self {
+            Country::Germany => "DE",
+            Country::Austria => "AT",
+            Country::Switzerland => "CH",
+        }
+    }
+
+    fn regions(&self) -> &'static [&'static str] {
+        match self {
+            Country::Germany => GERMAN_STATES,
+            // the days of the patron saints of the states are not public holidays
+            Country::Austria => &[],
+            Country::Switzerland => SWISS_CANTONS,
+        }
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        match self {
+            Country::Germany => GERMANY,
+            Country::Austria => AUSTRIA,
+            Country::Switzerland => SWITZERLAND,
+        }
+    }
+}
+
+impl Day {
+    /// Date of the day in `year` (`None` if it is out of the range of [NaiveDate])
+    fn date(&self, year: i32) -> Option<NaiveDate> {
+        match self {
+            Day::Fixed(month, day) => NaiveDate::from_ymd_opt(year, *month, *day),
+            Day::Easter(offset) => easter_sunday(year)?.checked_add_signed(Duration::days(*offset)),
+            Day::Repentance => {
+                let nov22 = NaiveDate::from_ymd_opt(year, 11, 22)?;
+                let days_since_wednesday = (nov22.weekday().num_days_from_monday() + 7
+                    - Weekday::Wed.num_days_from_monday()) % 7;
+                nov22.checked_sub_signed(Duration::days(days_since_wednesday as i64))
+            },
+        }
+    }
+}
+
+impl Regions {
+    /// Whether the holiday applies to `region` (holidays of the whole country only apply to `None`)
+    fn contains(&self, region: Option<&str>) -> bool {
+        match (self, region) {
+            (Regions::All, _) => true,
+            (Regions::Only(regions), Some(region)) => regions.contains(&region),
+            (Regions::Except(regions), Some(region)) => !regions.contains(&region),
+            (_, None) => false,
+        }
+    }
+}
+
+fn display_date(date: &NaiveDate) -> String {
+    date.format("%Y-%m-%d %a").to_string()
+}
+
+/// Date of Easter Sunday in the Gregorian calendar (anonymous Gregorian algorithm)
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+impl Display for HolidayCalendar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.region {
+            Some(region) => write!(f, "{}-{}", self.country.code(), region),
+            None => write!(f, "{}", self.country.code()),
+        }
+    }
+}
+
+impl FromStr for HolidayCalendar {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_uppercase();
+        let (country_code, region) = match s.split_once('-') {
+            Some((country, region)) => (country, Some(region.to_owned())),
+            None => (s.as_str(), None),
+        };
+        let country = match country_code {
+            "DE" => Country::Germany,
+            "AT" => Country::Austria,
+            "CH" => Country::Switzerland,
+            _ => return Err(format!("unknown country '{}' (expected DE, AT or CH)", country_code)),
+        };
+        if let Some(region) = &region {
+            if country.regions().is_empty() {
+                return Err(format!("{} has no regions with their own holidays", country.code()));
+            }
+            if !country.regions().contains(&region.as_str()) {
+                return Err(format!("unknown region '{}' of {} (expected one of {})",
+                                   region, country.code(), country.regions().join(", ")));
+            }
+        }
+        Ok(HolidayCalendar { country, region })
+    }
+}
+
+impl TryFrom<String> for HolidayCalendar {
+    type Error = String;
+
+    fn try_from(calendar: String) -> Result<Self, Self::Error> {
+        calendar.parse()
+    }
+}
+
+impl From<HolidayCalendar> for String {
+    fn from(calendar: HolidayCalendar) -> Self {
+        calendar.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    /// Easter Sunday, Good Friday, Easter Monday, Ascension, Whit Monday and Corpus Christi of several years
+    const MOVABLE_FEASTS: &[[&str; 6]] = &[
+        ["1818-03-22", "1818-03-20", "1818-03-23", "1818-04-30", "1818-05-11", "1818-05-21"],
+        ["1943-04-25", "1943-04-23", "1943-04-26", "1943-06-03", "1943-06-14", "1943-06-24"],
+        ["2000-04-23", "2000-04-21", "2000-04-24", "2000-06-01", "2000-06-12", "2000-06-22"],
+        ["2019-04-21", "2019-04-19", "2019-04-22", "2019-05-30", "2019-06-10", "2019-06-20"],
+        ["2024-03-31", "2024-03-29", "2024-04-01", "2024-05-09", "2024-05-20", "2024-05-30"],
+        ["2025-04-20", "2025-04-18", "2025-04-21", "2025-05-29", "2025-06-09", "2025-06-19"],
+        ["2026-04-05", "2026-04-03", "2026-04-06", "2026-05-14", "2026-05-25", "2026-06-04"],
+        ["2038-04-25", "2038-04-23", "2038-04-26", "2038-06-03", "2038-06-14", "2038-06-24"],
+    ];
+
+    /// Names of the movable feasts (except Easter Sunday) in a calendar, `None` where they are no holiday
+    const CALENDARS: &[(&str, [Option<&str>; 5])] = &[
+        ("DE", [Some("Karfreitag"), Some("Ostermontag"), Some("Christi Himmelfahrt"), Some("Pfingstmontag"), None]),
+        ("DE-BY", [Some("Karfreitag"), Some("Ostermontag"), Some("Christi Himmelfahrt"), Some("Pfingstmontag"), Some("Fronleichnam")]),
+        ("DE-BE", [Some("Karfreitag"), Some("Ostermontag"), Some("Christi Himmelfahrt"), Some("Pfingstmontag"), None]),
+        ("DE-NW", [Some("Karfreitag"), Some("Ostermontag"), Some("Christi Himmelfahrt"), Some("Pfingstmontag"), Some("Fronleichnam")]),
+        ("AT", [None, Some("Ostermontag"), Some("Christi Himmelfahrt"), Some("Pfingstmontag"), Some("Fronleichnam")]),
+        ("CH", [None, None, Some("Auffahrt"), None, None]),
+        ("CH-ZH", [Some("Karfreitag"), Some("Ostermontag"), Some("Auffahrt"), Some("Pfingstmontag"), None]),
+        ("CH-LU", [Some("Karfreitag"), Some("Ostermontag"), Some("Auffahrt"), Some("Pfingstmontag"), Some("Fronleichnam")]),
+        ("CH-TI", [None, Some("Ostermontag"), Some("Auffahrt"), Some("Pfingstmontag"), Some("Fronleichnam")]),
+        ("CH-VS", [None, None, Some("Auffahrt"), None, Some("Fronleichnam")]),
+    ];
+
+    #[test]
+    fn easter_sunday_of_known_years() {
+        for feasts in MOVABLE_FEASTS {
+            let easter = date(feasts[0]);
+            assert_eq!(easter_sunday(easter.year()), Some(easter));
+        }
+    }
+
+    #[test]
+    fn movable_feasts_of_each_region() {
+        for (calendar, names) in CALENDARS {
+            let calendar: HolidayCalendar = calendar.parse().unwrap();
+            for feasts in MOVABLE_FEASTS {
+                for (day, name) in feasts[1..].iter().zip(names) {
+                    let expected = name.map(|name| Holiday { date: date(day), name: name.to_owned() });
+                    assert_eq!(calendar.holiday(date(day)), expected, "{} on {}", calendar, day);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn easter_sunday_is_only_a_holiday_in_brandenburg() {
+        let easter = date("2024-03-31");
+        assert_eq!(HolidayCalendar::from_str("DE-BB").unwrap().holiday(easter).unwrap().name, "Ostersonntag");
+        assert_eq!(HolidayCalendar::from_str("DE-BY").unwrap().holiday(easter), None);
+    }
+
+    #[test]
+    fn day_of_repentance_is_the_wednesday_before_november_23rd() {
+        let saxony: HolidayCalendar = "DE-SN".parse().unwrap();
+        for day in ["2022-11-16", "2023-11-22", "2024-11-20", "2025-11-19"] {
+            assert_eq!(saxony.holiday(date(day)).unwrap().name, "Buß- und Bettag");
+        }
+        assert_eq!(HolidayCalendar::from_str("DE-BY").unwrap().holiday(date("2024-11-20")), None);
+    }
+
+    #[test]
+    fn holidays_only_apply_since_they_were_introduced() {
+        let berlin: HolidayCalendar = "DE-BE".parse().unwrap();
+        assert_eq!(berlin.holiday(date("2018-03-08")), None);
+        assert_eq!(berlin.holiday(date("2019-03-08")).unwrap().name, "Internationaler Frauentag");
+    }
+
+    #[test]
+    fn holidays_out_of_the_date_range_are_left_out() {
+        let bavaria: HolidayCalendar = "DE-BY".parse().unwrap();
+        assert!(bavaria.holidays(300000).is_empty());
+        assert!(bavaria.holidays(i32::MAX).is_empty());
+    }
+
+    #[test]
+    fn unknown_regions_are_rejected() {
+        assert!("DE-XX".parse::<HolidayCalendar>().is_err());
+        assert!("AT-W".parse::<HolidayCalendar>().is_err());
+        assert!("FR".parse::<HolidayCalendar>().is_err());
+        assert_eq!("ch-zh".parse::<HolidayCalendar>().unwrap().to_string(), "CH-ZH");
+    }
+}
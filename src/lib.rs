@@ -0,0 +1,35 @@
+//! Core of the foliot time tracker
+//!
+//! The [Tracker] gives access to the recorded data, which is split into [Namespace]s.
+//! Each namespace holds a list of [Entry]s, at most one running clock ([ClockinTimestamp])
+//! and its [NamespaceSettings].
+
+mod absence;
+mod config;
+mod duration;
+mod entry;
+pub mod formats;
+mod holidays;
+pub mod storage;
+mod schedule;
+mod settings;
+mod summary;
+mod time;
+mod tracker;
+
+pub use absence::{Absence, AbsenceKind, VacationSummary};
+pub use config::{Config, Defaults};
+pub use duration::HumanDuration;
+pub use entry::{Break, ClockinTimestamp, Entry, TableEntry};
+pub use holidays::{Holiday, HolidayCalendar};
+pub use schedule::Schedule;
+pub use settings::{NamespaceSettings, Target};
+pub use storage::{Storage, StorageKind};
+pub use summary::{summarize, Balance, Grouping, SummaryTableItem};
+pub use time::{duration_from_hours, local_datetime, now, parse_datetime, parse_duration, parse_end_time, parse_time, parse_time_range};
+pub use tracker::{ClockStatus, ClockoutDetails, ImportOutcome, MergeSummary, Namespace, NamespaceInfo, OverlapResolution, Tracker};
+
+/// Compiles the examples of the README, so they are kept up to date with the API
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+pub struct ReadmeDoctests;
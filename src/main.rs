@@ -1,14 +1,16 @@
 use chrono::offset::Local;
-use chrono::{DateTime, DurationRound, NaiveDateTime, TimeZone, NaiveDate, NaiveTime, Months};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime};
 use clap::Parser;
+use foliot::*;
 use regex::Regex;
-use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use serde::Serialize;
 use std::env;
 use std::fmt::Display;
 use std::fs;
+use std::io::{self, Write};
 use std::iter;
-use std::ops::Add;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::*;
 use std::process;
 use tabled::*;
@@ -19,27 +21,161 @@ use tabled::object::*;
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
-    /// The namespace to apply the command to
-    #[clap(short, long, default_value_t = String::from(DEFAULT_NAMESPACE))]
-    namespace: String,
-    // TODO handle empty values
+    /// The namespace to apply the command to (show, summarize and status accept it multiple times) [default: default]
+    #[clap(short = 'n', long = "namespace")]
+    namespaces: Vec<String>,
+
+    /// Apply show, summarize or status to all namespaces
+    #[clap(long, global = true)]
+    all_namespaces: bool,
 
     /// Run `git commit -am "[<namespace>] <action>"` afterwards
-    #[clap(short, long)]
+    #[clap(short, long, overrides_with = "no_git_commit")]
     git_commit: bool,
 
+    /// Do not commit, even if enabled in the configuration
+    #[clap(long, overrides_with = "git_commit")]
+    no_git_commit: bool,
+
     /// Pull, rebase and push git repository afterwards
-    #[clap(short('p'), long)]
+    #[clap(short('p'), long, overrides_with = "no_git_push")]
     git_push: bool,
 
+    /// Do not push, even if enabled in the configuration
+    #[clap(long, overrides_with = "git_push")]
+    no_git_push: bool,
+
+    /// Output format for show, summarize and status
+    #[clap(short, long, value_enum, global = true, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
     #[clap(subcommand)]
     command: Command,
+
+    /// Defaults read from the configuration file
+    #[clap(skip)]
+    config: Config,
 }
 
-/// Record of a started clock
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-struct ClockinTimestamp {
-    start_time: DateTime<Local>,
+/// Format to print data in
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    /// Human-readable tables
+    Table,
+
+    /// JSON
+    Json,
+
+    /// YAML
+    Yaml,
+}
+
+/// Format to export entries to
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum ExportFormat {
+    /// Comma-separated values (see `--delimiter`)
+    Csv,
+
+    /// iCalendar file with one event per entry
+    Ics,
+}
+
+/// Format to import entries from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum ImportFormat {
+    /// Comma-separated values with a header row (see `--delimiter`)
+    Csv,
+
+    /// JSON written by `timew export`
+    Timewarrior,
+
+    /// JSON written by `watson log --json` or Watson's frames file
+    Watson,
+
+    /// CSV of a detailed report from Toggl Track
+    TogglCsv,
+}
+
+/// Summary for machine-readable output
+#[derive(Serialize)]
+struct SummaryOutput {
+    grouping: String,
+    groups: Vec<SummaryTableItem>,
+    total: SummaryTableItem,
+}
+
+/// Absences and vacation days for machine-readable output
+#[derive(Serialize)]
+struct AbsencesOutput {
+    absences: Vec<Absence>,
+    vacation: VacationSummary,
+}
+
+/// Summary of multiple namespaces for machine-readable output
+#[derive(Serialize)]
+struct NamespacesSummaryOutput {
+    grouping: String,
+    namespaces: Vec<NamespaceSummary>,
+    total: SummaryTableItem,
+}
+
+/// Summary of a single namespace among others
+#[derive(Serialize)]
+struct NamespaceSummary {
+    namespace: String,
+    groups: Vec<SummaryTableItem>,
+    total: SummaryTableItem,
+}
+
+/// Entry of a report spanning multiple namespaces
+#[derive(Tabled, Serialize)]
+struct NamespacedTableEntry {
+    namespace: String,
+
+    #[tabled(inline)]
+    #[serde(flatten)]
+    entry: TableEntry,
+}
+
+/// Summary row of a report spanning multiple namespaces
+#[derive(Tabled)]
+struct NamespacedSummaryItem {
+    namespace: String,
+
+    #[tabled(inline)]
+    item: SummaryTableItem,
+}
+
+/// Options describing a new entry
+#[derive(Clone, Debug, Default, PartialEq, clap::Args)]
+struct DetailArgs {
+    /// Tag the entry (may be given multiple times)
+    #[clap(long = "tag")]
+    tags: Vec<String>,
+
+    /// Project the entry belongs to
+    #[clap(long)]
+    project: Option<String>,
+
+    /// Task within the project
+    #[clap(long)]
+    task: Option<String>,
+}
+
+/// Options to select the entries to display
+#[derive(Clone, Debug, Default, PartialEq, clap::Args)]
+struct FilterArgs {
+    /// Filter entries with regex
+    #[clap(short, long)]
+    filter: Option<String>,
+
+    /// Only include entries with this tag (may be given multiple times)
+    #[clap(long = "tag")]
+    tags: Vec<String>,
+
+    /// Only include entries of this project
+    #[clap(long)]
+    project: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, clap::Subcommand)]
@@ -47,30 +183,118 @@ enum Command {
     /// Abort current timer
     Abort {},
 
+    /// Record days of absence like vacation or sick leave
+    Absence {
+        #[clap(subcommand)]
+        command: AbsenceCommand,
+    },
+
+    /// Change the start, end or comment of an entry
+    Amend {
+        /// ID of the entry (or an unambiguous prefix)
+        id: String,
+
+        /// New starting time (e.g. 14:30, yesterday 9:30, 2h ago or 2015-09-18T23:56)
+        #[clap(long, value_parser = parse_datetime, allow_hyphen_values = true)]
+        start: Option<NaiveDateTime>,
+
+        /// New ending time (e.g. 14:30, yesterday 9:30, 2h ago or 2015-09-18T23:56)
+        #[clap(long, value_parser = parse_datetime, allow_hyphen_values = true)]
+        end: Option<NaiveDateTime>,
+
+        /// New comment
+        #[clap(short, long)]
+        comment: Option<String>,
+    },
+
+    /// Change the start time, comment, tags, project or task of the running clock
+    AmendRunning {
+        /// New starting time (e.g. 14:30, yesterday 9:30, 2h ago or 2015-09-18T23:56)
+        #[clap(short, long, value_parser = parse_datetime, allow_hyphen_values = true)]
+        starting: Option<NaiveDateTime>,
+
+        /// Replace the comment of the clock
+        #[clap(short, long)]
+        comment: Option<String>,
+
+        /// Remove a tag from the clock (may be given multiple times)
+        #[clap(long = "remove-tag")]
+        remove_tags: Vec<String>,
+
+        /// Tags to add, project and task to replace
+        #[clap(flatten)]
+        details: DetailArgs,
+    },
+
+    /// Compare the worked hours with the target of the namespace
+    Balance {
+        /// Last day to take into account (format: %Y-%m-%d, defaults to today)
+        #[clap(long)]
+        until: Option<NaiveDate>,
+    },
+
     /// Clock an arbitrary time
     Clock {
-        /// Number of hours to log (floating point number)
-        hours: f64,
+        /// Number of hours to log (floating point number) or a time range (e.g. 09:00-12:30)
+        span: Option<String>,
 
-        /// Starting time (format: %Y-%m-%dT%H:%M:%S, eg. 2015-09-18T23:56:04)
-        #[clap(short, long, value_parser = parse_starting_value)]
+        /// Starting time (e.g. 14:30, yesterday 9:30, 2h ago or 2015-09-18T23:56)
+        #[clap(short, long, alias = "start", value_parser = parse_datetime, allow_hyphen_values = true)]
         starting: Option<NaiveDateTime>,
 
+        /// Ending time, instead of the hours (a time without a day refers to the day of --starting)
+        #[clap(long, requires = "starting", allow_hyphen_values = true)]
+        end: Option<String>,
+
         /// Comment on the clock entry
         comment: Option<String>,
+
+        #[clap(flatten)]
+        details: DetailArgs,
     },
 
     /// Start the timer
     Clockin {
-        /// Starting time (format: %Y-%m-%dT%H:%M:%S, eg. 2015-09-18T23:56:04)
-        #[clap(short, long, value_parser = parse_starting_value)]
+        /// Starting time (e.g. 14:30, yesterday 9:30, 2h ago or 2015-09-18T23:56)
+        #[clap(short, long, value_parser = parse_datetime, allow_hyphen_values = true)]
         starting: Option<NaiveDateTime>,
+
+        /// Comment on the entry (a comment given at clockout is appended)
+        #[clap(short, long)]
+        comment: Option<String>,
+
+        #[clap(flatten)]
+        details: DetailArgs,
     },
 
     /// Stop the timer and add save the entry
     Clockout {
         /// Comment on the clock entry
         comment: Option<String>,
+
+        /// Stop the clock at this time instead of now (e.g. 17:45 or yesterday 18:00)
+        #[clap(long, value_parser = parse_datetime, allow_hyphen_values = true)]
+        at: Option<NaiveDateTime>,
+
+        /// Stop the clock this long ago (e.g. 20m or 1h30m)
+        #[clap(long, value_parser = parse_duration, conflicts_with = "at")]
+        ago: Option<chrono::Duration>,
+
+        /// Tags, project and task (tags are added to the ones given at clockin)
+        #[clap(flatten)]
+        details: DetailArgs,
+    },
+
+    /// Get or set the defaults in the configuration file
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommand,
+    },
+
+    /// Delete an entry
+    Delete {
+        /// ID of the entry (or an unambiguous prefix)
+        id: String,
     },
 
     /// Edit entries or clockin file
@@ -80,12 +304,81 @@ enum Command {
         clockin: bool,
     },
 
+    /// Export entries to a file
+    Export {
+        /// File format
+        #[clap(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+
+        /// Write to this file instead of stdout
+        #[clap(long)]
+        file: Option<PathBuf>,
+
+        /// Field delimiter for CSV
+        #[clap(short, long, default_value_t = ',')]
+        delimiter: char,
+
+        /// Only export entries starting on or after this date (format: %Y-%m-%d)
+        #[clap(long)]
+        from: Option<NaiveDate>,
+
+        /// Only export entries starting on or before this date (format: %Y-%m-%d)
+        #[clap(long)]
+        to: Option<NaiveDate>,
+    },
+
     /// Execute git command in foliot directory
     Git {
         /// Arguments to pass to git
         git_args: Vec<String>,
     },
 
+    /// Select or list the public holidays of the namespace
+    Holidays {
+        #[clap(subcommand)]
+        command: HolidaysCommand,
+    },
+
+    /// Import entries from a file
+    Import {
+        /// File format
+        #[clap(long = "from", value_enum, default_value_t = ImportFormat::Csv)]
+        format: ImportFormat,
+
+        /// File to import
+        file: PathBuf,
+
+        /// Field delimiter for CSV
+        #[clap(short, long, default_value_t = ',')]
+        delimiter: char,
+
+        /// Only show what would be imported
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Join two entries into one, the time between them becoming a break
+    Join {
+        /// ID of the first entry (or an unambiguous prefix)
+        id: String,
+
+        /// ID of the second entry (or an unambiguous prefix)
+        other_id: String,
+    },
+
+    /// Convert all namespaces to another storage backend
+    Migrate {
+        /// Storage backend to migrate to (yaml or sqlite)
+        #[clap(long)]
+        to: StorageKind,
+    },
+
+    /// List and manage namespaces
+    Namespace {
+        #[clap(subcommand)]
+        command: NamespaceCommand,
+    },
+
     /// Print path to the data to output
     Path {
         /// Print path to the given namespace entry file
@@ -93,156 +386,477 @@ enum Command {
         namespace: Option<String>,
     },
 
+    /// Pause the running clock (breaks do not count towards the entry)
+    Pause {},
+
+    /// Resume the paused clock
+    Resume {},
+
     /// Show entries in a table
     Show {
-        /// Filter entries with regex
-        #[clap(short, long)]
-        filter: Option<String>,
+        #[clap(flatten)]
+        filter: FilterArgs,
 
-        /// Only show last n entries (0 to show all)
-        #[clap(short, long, default_value_t = 30)]
-        tail: usize,
+        /// Only show last n entries (0 to show all) [default: 30]
+        #[clap(short, long)]
+        tail: Option<usize>,
 
-        /// Wrap content column at x chars
-        #[clap(short, long, default_value_t = 80)]
-        wrap: usize,
+        /// Wrap content column at x chars [default: 80]
+        #[clap(short, long)]
+        wrap: Option<usize>,
     },
 
     /// Print current status of clock timer
     Status {},
 
-    /// Create a per-month summary
+    /// Split an entry into two
+    Split {
+        /// ID of the entry (or an unambiguous prefix)
+        id: String,
+
+        /// Time to split the entry at (e.g. 14:30, yesterday 9:30, 2h ago or 2015-09-18T23:56)
+        #[clap(long, value_parser = parse_datetime, allow_hyphen_values = true)]
+        at: NaiveDateTime,
+    },
+
+    /// Create a summary per period or project
     Summarize {
-        /// Filter entries with regex
+        #[clap(flatten)]
+        filter: FilterArgs,
+
+        /// Group entries by "day", "week", "month", "quarter", "year" or "project"
+        #[clap(short, long, default_value_t = Grouping::Month)]
+        by: Grouping,
+
+        /// Only count the elapsed part of the current period for its hours per week and working days
+        #[clap(long)]
+        partial: bool,
+
+        /// Only show last n entries (0 to show all) [default: 30]
         #[clap(short, long)]
-        filter: Option<String>,
+        tail: Option<usize>,
+    },
+
+    /// Stop the running clock and start one for another namespace at the same time
+    Switch {
+        /// Namespace to start the clock for
+        target: String,
+
+        /// Comment on the entry of the stopped clock
+        comment: Option<String>,
+
+        /// Tags, project and task of the new clock
+        #[clap(flatten)]
+        details: DetailArgs,
+    },
+
+    /// Set or show the expected working hours of the namespace
+    Target {
+        /// Hours per week, spread evenly over Monday to Friday
+        #[clap(long, conflicts_with = "weekdays")]
+        weekly: Option<f64>,
 
-        /// Only show last n entries (0 to show all)
-        #[clap(short, long, default_value_t = 30)]
-        tail: usize,
+        /// Hours per weekday from Monday to Sunday (e.g. 8,8,8,8,6,0,0)
+        #[clap(long, value_delimiter = ',')]
+        weekdays: Vec<f64>,
+
+        /// First day the target applies to (format: %Y-%m-%d, defaults to today for a new target)
+        #[clap(long)]
+        since: Option<NaiveDate>,
+
+        /// Balance in hours from before the target applies (negative if behind)
+        #[clap(long, allow_negative_numbers = true)]
+        carry_over: Option<f64>,
+
+        /// Remove the target
+        #[clap(long, conflicts_with_all = ["weekly", "weekdays", "since", "carry_over"])]
+        remove: bool,
     },
 }
 
-/// Human readable duration (no more precise than a minute)
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-struct HumanDuration {
-    hours: i64,
-    minutes: i64,
+#[derive(Clone, Debug, PartialEq, clap::Subcommand)]
+enum AbsenceCommand {
+    /// Add an absence for a day or a range of days
+    Add {
+        /// Day of the absence (format: %Y-%m-%d)
+        date: NaiveDate,
+
+        /// Last day of a longer absence (days without expected hours are skipped)
+        #[clap(long)]
+        to: Option<NaiveDate>,
+
+        /// Kind of the absence ("vacation", "sick", "holiday" or any other word)
+        #[clap(short, long, default_value_t = AbsenceKind::Vacation)]
+        kind: AbsenceKind,
+
+        /// Comment on the absence
+        comment: Option<String>,
+    },
+
+    /// List absences and the vacation days taken
+    List {
+        /// Only list absences of this year
+        #[clap(long)]
+        year: Option<i32>,
+    },
+
+    /// Remove the absences of a day or a range of days
+    Remove {
+        /// Day of the absence (format: %Y-%m-%d)
+        date: NaiveDate,
+
+        /// Last day of the range to remove
+        #[clap(long)]
+        to: Option<NaiveDate>,
+    },
+
+    /// Set the number of vacation days per year
+    Quota {
+        days: u32,
+    },
 }
 
-/// Clock entry
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-struct Entry {
-    /// Time the clock was started
-    start_time: DateTime<Local>,
+#[derive(Clone, Debug, PartialEq, clap::Subcommand)]
+enum ConfigCommand {
+    /// Print the value of a key (e.g. `tail` or `namespaces.work.tail`)
+    Get {
+        key: String,
+    },
+
+    /// Set a key to a value
+    Set {
+        key: String,
+        value: String,
+    },
 
-    /// Time the clock ended
-    end_time: DateTime<Local>,
+    /// Remove a key, so the built-in default applies again
+    Unset {
+        key: String,
+    },
 
-    /// Optional comment
-    comment: Option<String>,
+    /// List all keys that are set
+    List {},
 }
 
-/// Entry formatted for displaying in human-readable form
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Tabled)]
-struct TableEntry {
-    date: NaiveDate,
-    from: NaiveTime,
-    to: NaiveTime,
-    duration: HumanDuration,
-    comment: String,
+#[derive(Clone, Debug, PartialEq, clap::Subcommand)]
+enum HolidaysCommand {
+    /// List the public holidays of a year
+    List {
+        /// Year to list the holidays of (defaults to the current one)
+        #[clap(long)]
+        year: Option<i32>,
+    },
+
+    /// Select the holidays of a country (DE, AT or CH) or one of its regions (e.g. DE-BY or CH-ZH)
+    Set {
+        calendar: HolidayCalendar,
+    },
+
+    /// Do not take public holidays into account
+    Unset {},
 }
 
-/// Entry formatted for displaying a summary for a month
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Tabled)]
-struct SummaryTableItem {
-    month: String,
+#[derive(Clone, Debug, PartialEq, clap::Subcommand)]
+enum NamespaceCommand {
+    /// List all namespaces
+    List {},
+
+    /// Show number of entries, first and last entry and whether a clock is running
+    Info {
+        /// Namespace to describe (defaults to `--namespace`)
+        name: Option<String>,
+    },
+
+    /// Rename a namespace
+    Rename {
+        from: String,
+        to: String,
+    },
 
-    #[tabled(rename = "total hours")]
-    total_hours: HumanDuration,
+    /// Move all entries of a namespace into another one
+    Merge {
+        /// Namespace to merge (removed afterwards)
+        from: String,
 
-    #[tabled(rename = "hours / week")]
-    hours_per_week: String,
+        /// Namespace to merge into
+        into: String,
 
-    days: usize,
+        /// How to handle overlapping entries ("abort", "keep-target" or "keep-source")
+        #[clap(long, default_value_t = OverlapResolution::Abort)]
+        on_overlap: OverlapResolution,
+    },
 
-    #[tabled(rename = "entries")]
-    nitems: usize,
-}
+    /// Delete a namespace with all of its entries
+    Delete {
+        name: String,
 
+        /// Do not ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+}
 
 const DEFAULT_NAMESPACE: &str = "default";
-const XDG_DIR_PREFIX: &str = "foliot";
 
+/// Number of entries shown by `show` and `summarize` unless configured otherwise
+const DEFAULT_TAIL: usize = 30;
+
+/// Width the columns of `show` are wrapped at unless configured otherwise
+const DEFAULT_WRAP: usize = 80;
+
+/// Prefix of the lines describing an error at the top of a file opened by `edit`
+const EDIT_ERROR_PREFIX: &str = "# foliot: ";
+
+/// Environment variable that forbids running clocks in more than one namespace if set to `1` or `true`
+const SINGLE_CLOCK_VAR: &str = "FOLIOT_SINGLE_CLOCK";
+
+impl FilterArgs {
+    /// Only keep the entries matching all filters
+    fn apply(&self, mut entries: Vec<Entry>) -> Result<Vec<Entry>, String> {
+        if let Some(filter_str) = &self.filter {
+            let re = Regex::new(filter_str).map_err(|e| e.to_string())?;
+            entries.retain(|e| e.matches(&re));
+        }
+        entries.retain(|e| self.tags.iter().all(|t| e.has_tag(t)));
+        if let Some(project) = &self.project {
+            entries.retain(|e| e.project.as_ref() == Some(project));
+        }
+        Ok(entries)
+    }
+
+    /// The schedule to summarize the filtered entries with
+    ///
+    /// The target is dropped if any filter is given, as it applies to all entries of the namespace.
+    fn schedule(&self, mut schedule: Schedule) -> Schedule {
+        if *self != FilterArgs::default() {
+            schedule.target = None;
+        }
+        schedule
+    }
+}
+
+impl Args {
+    /// The namespace to apply a single-namespace command to
+    fn namespace(&self) -> &str {
+        &self.namespaces[0]
+    }
+
+    /// Configured defaults of the selected namespace (the global ones for multiple namespaces)
+    fn defaults(&self) -> Defaults {
+        if self.multiple_namespaces() {
+            self.config.defaults.clone()
+        } else {
+            self.config.defaults(self.namespace())
+        }
+    }
+
+    /// Whether to commit the changes, by flag or configuration
+    fn git_commit(&self) -> bool {
+        match (self.git_commit, self.no_git_commit) {
+            (true, _) => true,
+            (_, true) => false,
+            _ => self.defaults().git_commit.unwrap_or(false),
+        }
+    }
+
+    /// Whether to push the commit, by flag or configuration
+    fn git_push(&self) -> bool {
+        match (self.git_push, self.no_git_push) {
+            (true, _) => true,
+            (_, true) => false,
+            _ => self.defaults().git_push.unwrap_or(false),
+        }
+    }
+
+    /// Number of entries to show, by flag or configuration
+    fn tail(&self, tail: Option<usize>) -> usize {
+        tail.or(self.defaults().tail).unwrap_or(DEFAULT_TAIL)
+    }
+
+    /// Width to wrap columns at, by flag or configuration
+    fn wrap(&self, wrap: Option<usize>) -> usize {
+        wrap.or(self.defaults().wrap).unwrap_or(DEFAULT_WRAP)
+    }
+
+    /// Whether the command should be applied to more than one namespace
+    fn multiple_namespaces(&self) -> bool {
+        self.all_namespaces || self.namespaces.len() > 1
+    }
+
+    /// All namespaces selected with `--namespace` or `--all-namespaces`
+    fn selected_namespaces(&self, tracker: &Tracker) -> Result<Vec<String>, String> {
+        if self.all_namespaces {
+            return tracker.namespaces();
+        }
+
+        let mut namespaces = Vec::new();
+        for namespace in &self.namespaces {
+            if !namespaces.contains(namespace) {
+                namespaces.push(namespace.clone());
+            }
+        }
+        Ok(namespaces)
+    }
+}
 
 impl Command {
+    /// Whether the command can be applied to multiple namespaces at once
+    fn supports_multiple_namespaces(&self) -> bool {
+        matches!(self, Self::Show { .. } | Self::Summarize { .. } | Self::Status {})
+    }
+
     /// Execute a command with the given arguments
-    fn execute(&self, args: &Args) -> Result<(), String> {
+    fn execute(&self, tracker: &mut Tracker, args: &Args) -> Result<(), String> {
         match self {
-            Self::Abort {} => abort(args),
-            Self::Clockin { starting } => clockin(*starting, args),
-            Self::Clockout { comment } => clockout(comment.clone(), args),
-            Self::Clock { hours, starting, comment } => clock_duration(*hours, *starting, comment.clone(), args),
-            Self::Edit { clockin } => edit(*clockin, args),
-            Self::Git { git_args } => git(git_args, args),
-            Self::Path { namespace } => print_path(namespace.clone(), args),
-            Self::Show { filter, tail, wrap } => show(filter, *tail, *wrap, args),
-            Self::Status {} => status(args),
-            Self::Summarize { filter, tail } => summarize(filter, *tail, args),
+            Self::Abort {} => abort(tracker, args),
+            Self::Absence { command } => absence(command, tracker, args),
+            Self::Amend { id, start, end, comment } => amend(id, *start, *end, comment.clone(), tracker, args),
+            Self::AmendRunning { starting, comment, remove_tags, details } =>
+                amend_running(*starting, comment.clone(), remove_tags, details.clone(), tracker, args),
+            Self::Balance { until } => balance(*until, tracker, args),
+            Self::Clockin { starting, comment, details } => clockin(*starting, comment.clone(), details.clone(), tracker, args),
+            Self::Clockout { comment, at, ago, details } => clockout(comment.clone(), *at, *ago, details.clone(), tracker, args),
+            Self::Clock { span, starting, end, comment, details } =>
+                clock_span(span.as_deref(), *starting, end.as_deref(), comment.clone(), details.clone(), tracker, args),
+            Self::Config { command } => config(command, args),
+            Self::Delete { id } => delete(id, tracker, args),
+            Self::Edit { clockin } => edit(*clockin, tracker, args),
+            Self::Export { format, file, delimiter, from, to } =>
+                export(*format, file, *delimiter, *from, *to, tracker, args),
+            Self::Git { git_args } => git(git_args, tracker, args),
+            Self::Holidays { command } => holidays(command, tracker, args),
+            Self::Import { format, file, delimiter, dry_run } => import(*format, file, *delimiter, *dry_run, tracker, args),
+            Self::Join { id, other_id } => join(id, other_id, tracker, args),
+            Self::Migrate { to } => migrate(*to, tracker, args),
+            Self::Namespace { command } => namespace(command, tracker, args),
+            Self::Path { namespace } => print_path(namespace.clone(), tracker, args),
+            Self::Pause {} => pause(tracker, args),
+            Self::Resume {} => resume(tracker, args),
+            Self::Show { filter, tail, wrap } => show(filter, args.tail(*tail), args.wrap(*wrap), tracker, args),
+            Self::Status {} => status(tracker, args),
+            Self::Split { id, at } => split(id, *at, tracker, args),
+            Self::Summarize { filter, by, partial, tail } => summarize(filter, *by, *partial, args.tail(*tail), tracker, args),
+            Self::Switch { target, comment, details } => switch(target, comment.clone(), details.clone(), tracker, args),
+            Self::Target { weekly, weekdays, since, carry_over, remove } =>
+                target(*weekly, weekdays, *since, *carry_over, *remove, tracker, args),
         }
     }
 }
 
-impl ClockinTimestamp {
-    /// Creates a [ClockinTimestamp] referencing the date and time of the function call
-    fn now() -> Self {
-        return ClockinTimestamp { start_time: now() }
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::Ics => write!(f, "ics"),
+        }
     }
+}
 
-    /// Creates a [ClockinTimestamp] referencing a certain starting time
-    fn starting(time: &NaiveDateTime) -> Self {
-        return ClockinTimestamp { start_time: Local.from_local_datetime(time).unwrap() }
+impl Display for ImportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::Timewarrior => write!(f, "timewarrior"),
+            Self::Watson => write!(f, "watson"),
+            Self::TogglCsv => write!(f, "toggl-csv"),
+        }
     }
+}
 
-    /// Relative path to the file that contains the last clockin timestamp
-    fn relative_path(namespace: &str) -> PathBuf {
-        PathBuf::from(format!("{}-clockin", namespace))
-            .with_extension("yaml")
+impl Display for AbsenceCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add { date, to, kind, comment } => {
+                write!(f, "add {}", date)?;
+                if let Some(to) = to {
+                    write!(f, " --to {}", to)?;
+                }
+                write!(f, " --kind \"{}\"", kind)?;
+                if let Some(comment) = comment {
+                    write!(f, " \"{}\"", comment)?;
+                }
+                Ok(())
+            },
+            Self::List { year } => match year {
+                Some(year) => write!(f, "list --year {}", year),
+                None => write!(f, "list"),
+            },
+            Self::Remove { date, to } => match to {
+                Some(to) => write!(f, "remove {} --to {}", date, to),
+                None => write!(f, "remove {}", date),
+            },
+            Self::Quota { days } => write!(f, "quota {}", days),
+        }
     }
 }
 
-impl HumanDuration {
-    fn zero() -> HumanDuration {
-        HumanDuration {
-            hours: 0,
-            minutes: 0,
+impl Display for ConfigCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Get { key } => write!(f, "get \"{}\"", key),
+            Self::Set { key, value } => write!(f, "set \"{}\" \"{}\"", key, value),
+            Self::Unset { key } => write!(f, "unset \"{}\"", key),
+            Self::List {} => write!(f, "list"),
         }
     }
 }
 
-impl Entry {
-    /// Create a new clock entry
-    fn create(start_time: DateTime<Local>, end_time: DateTime<Local>, comment: Option<String>) -> Self {
-        return Entry {
-            start_time, end_time, comment,
-        };
+impl Display for HolidaysCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::List { year } => match year {
+                Some(year) => write!(f, "list --year {}", year),
+                None => write!(f, "list"),
+            },
+            Self::Set { calendar } => write!(f, "set {}", calendar),
+            Self::Unset {} => write!(f, "unset"),
+        }
     }
+}
 
-    /// Relative path to the file that entries are collected in
-    fn relative_path(namespace: &str) -> PathBuf {
-        PathBuf::from(format!("{}", namespace))
-            .with_extension("yaml")
+impl Display for NamespaceCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::List {} => write!(f, "list"),
+            Self::Info { name } => match name {
+                Some(name) => write!(f, "info \"{}\"", name),
+                None => write!(f, "info"),
+            },
+            Self::Rename { from, to } => write!(f, "rename \"{}\" \"{}\"", from, to),
+            Self::Merge { from, into, on_overlap } =>
+                write!(f, "merge \"{}\" \"{}\" --on-overlap {}", from, into, on_overlap),
+            Self::Delete { name, .. } => write!(f, "delete \"{}\"", name),
+        }
     }
+}
 
-    /// Total duration of the entry
-    fn duration(&self) -> HumanDuration {
-        (self.end_time - self.start_time).into()
+impl Display for DetailArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for tag in &self.tags {
+            write!(f, " --tag \"{}\"", tag)?;
+        }
+        if let Some(project) = &self.project {
+            write!(f, " --project \"{}\"", project)?;
+        }
+        if let Some(task) = &self.task {
+            write!(f, " --task \"{}\"", task)?;
+        }
+        Ok(())
     }
 }
 
-impl Display for HumanDuration {
+impl Display for FilterArgs {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:02}:{:02}h", self.hours, self.minutes)
+        if let Some(filter_str) = &self.filter {
+            write!(f, " --filter \"{}\"", filter_str)?;
+        }
+        for tag in &self.tags {
+            write!(f, " --tag \"{}\"", tag)?;
+        }
+        if let Some(project) = &self.project {
+            write!(f, " --project \"{}\"", project)?;
+        }
+        Ok(())
     }
 }
 
@@ -250,29 +864,104 @@ impl Display for Command {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Abort {} => write!(f, "abort"),
-            Self::Clockin { starting } => match starting {
-                Some(time) => write!(f, "clockin --starting \"{}\"", time),
-                None => write!(f, "clockin"),
+            Self::Absence { command } => write!(f, "absence {}", command),
+            Self::Amend { id, start, end, comment } => {
+                write!(f, "amend {}", id)?;
+                if let Some(start) = start {
+                    write!(f, " --start \"{}\"", start)?;
+                }
+                if let Some(end) = end {
+                    write!(f, " --end \"{}\"", end)?;
+                }
+                if let Some(comment) = comment {
+                    write!(f, " --comment \"{}\"", comment)?;
+                }
+                Ok(())
+            },
+            Self::AmendRunning { starting, comment, remove_tags, details } => {
+                write!(f, "amend-running")?;
+                if let Some(time) = starting {
+                    write!(f, " --starting \"{}\"", time)?;
+                }
+                if let Some(comment) = comment {
+                    write!(f, " --comment \"{}\"", comment)?;
+                }
+                for tag in remove_tags {
+                    write!(f, " --remove-tag \"{}\"", tag)?;
+                }
+                write!(f, "{}", details)
+            },
+            Self::Balance { until } => match until {
+                Some(until) => write!(f, "balance --until {}", until),
+                None => write!(f, "balance"),
+            },
+            Self::Clockin { starting, comment, details } => {
+                write!(f, "clockin")?;
+                if let Some(time) = starting {
+                    write!(f, " --starting \"{}\"", time)?;
+                }
+                if let Some(comment) = comment {
+                    write!(f, " --comment \"{}\"", comment)?;
+                }
+                write!(f, "{}", details)
             },
-            Self::Clockout { comment } => match comment {
-                Some(comment) => write!(f, "clockout \"{}\"", comment),
-                None => write!(f, "clockout"),
+            Self::Clockout { comment, at, ago, details } => {
+                write!(f, "clockout{}", details)?;
+                if let Some(at) = at {
+                    write!(f, " --at \"{}\"", at)?;
+                }
+                if let Some(ago) = ago {
+                    write!(f, " --ago {}m", ago.num_minutes())?;
+                }
+                if let Some(comment) = comment {
+                    write!(f, " \"{}\"", comment)?;
+                }
+                write!(f, "")
             },
-            Self::Clock { hours, starting, comment } => {
+            Self::Clock { span, starting, end, comment, details } => {
                 write!(f, "clock")?;
                 if let Some(time) = starting {
                     write!(f, " --starting \"{}\"", time)?;
                 }
-                write!(f, " {}", hours)?;
+                if let Some(end) = end {
+                    write!(f, " --end \"{}\"", end)?;
+                }
+                write!(f, "{}", details)?;
+                if let Some(span) = span {
+                    write!(f, " {}", span)?;
+                }
                 if let Some(comment) = comment {
                     write!(f, " \"{}\"", comment)?;
                 }
                 write!(f, "")
             },
+            Self::Config { command } => write!(f, "config {}", command),
+            Self::Delete { id } => write!(f, "delete {}", id),
             Self::Edit { clockin } => match clockin {
                 true => write!(f, "edit --clockin"),
                 false => write!(f, "edit"),
             },
+            Self::Export { format, file, delimiter, from, to } => {
+                write!(f, "export --format {} --delimiter \"{}\"", format, delimiter)?;
+                if let Some(file) = file {
+                    write!(f, " --file \"{}\"", file.to_string_lossy())?;
+                }
+                if let Some(from) = from {
+                    write!(f, " --from {}", from)?;
+                }
+                if let Some(to) = to {
+                    write!(f, " --to {}", to)?;
+                }
+                write!(f, "")
+            },
+            Self::Holidays { command } => write!(f, "holidays {}", command),
+            Self::Import { format, file, delimiter, dry_run } => {
+                write!(f, "import --from {} \"{}\" --delimiter \"{}\"", format, file.to_string_lossy(), delimiter)?;
+                if *dry_run {
+                    write!(f, " --dry-run")?;
+                }
+                Ok(())
+            },
             Self::Git { git_args } => {
                 write!(f, "git")?;
                 for arg in git_args {
@@ -280,135 +969,210 @@ impl Display for Command {
                 }
                 write!(f, "")
             },
+            Self::Join { id, other_id } => write!(f, "join {} {}", id, other_id),
+            Self::Migrate { to } => write!(f, "migrate --to {}", to),
+            Self::Namespace { command } => write!(f, "namespace {}", command),
             Self::Path { namespace } => match namespace {
                 Some(ns) => write!(f, "path --namespace \"{}\"", ns),
                 None => write!(f, "path"),
             },
+            Self::Pause {} => write!(f, "pause"),
+            Self::Resume {} => write!(f, "resume"),
             Self::Show { filter, tail, wrap } => {
-                write!(f, "show")?;
-                if let Some(filter_str) = filter {
-                    write!(f, " --filter \"{}\"", filter_str)?;
+                write!(f, "show{}", filter)?;
+                if let Some(tail) = tail {
+                    write!(f, " --tail {}", tail)?;
                 }
-                write!(f, " --tail {} --wrap {}", tail, wrap)
+                if let Some(wrap) = wrap {
+                    write!(f, " --wrap {}", wrap)?;
+                }
+                Ok(())
             },
             Self::Status {} => write!(f, "status"),
-            Self::Summarize { filter, tail } => {
-                write!(f, "summarize")?;
-                if let Some(filter_str) = filter {
-                    write!(f, " --filter \"{}\"", filter_str)?;
+            Self::Split { id, at } => write!(f, "split {} --at \"{}\"", id, at),
+            Self::Summarize { filter, by, partial, tail } => {
+                write!(f, "summarize{} --by {}", filter, by)?;
+                if *partial {
+                    write!(f, " --partial")?;
+                }
+                if let Some(tail) = tail {
+                    write!(f, " --tail {}", tail)?;
+                }
+                Ok(())
+            },
+            Self::Switch { target, comment, details } => {
+                write!(f, "switch{} \"{}\"", details, target)?;
+                if let Some(comment) = comment {
+                    write!(f, " \"{}\"", comment)?;
+                }
+                Ok(())
+            },
+            Self::Target { weekly, weekdays, since, carry_over, remove } => {
+                write!(f, "target")?;
+                if let Some(weekly) = weekly {
+                    write!(f, " --weekly {}", weekly)?;
                 }
-                write!(f, " --tail {}", tail)
+                if !weekdays.is_empty() {
+                    let weekdays: Vec<String> = weekdays.iter().map(|h| h.to_string()).collect();
+                    write!(f, " --weekdays {}", weekdays.join(","))?;
+                }
+                if let Some(since) = since {
+                    write!(f, " --since {}", since)?;
+                }
+                if let Some(carry_over) = carry_over {
+                    write!(f, " --carry-over {}", carry_over)?;
+                }
+                if *remove {
+                    write!(f, " --remove")?;
+                }
+                Ok(())
             },
         }
     }
 }
 
-impl Add for HumanDuration {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        let added_minutes = self.minutes + other.minutes;
-        HumanDuration {
-            hours: self.hours + other.hours + added_minutes / 60,
-            minutes: added_minutes % 60,
-        }
-    }
+/// Abort the currently running clock
+fn abort(tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let namespace = tracker.namespace(args.namespace());
+    namespace.abort()?;
+    println!("Aborting clock for namespace '{}'", args.namespace());
+    Ok(())
 }
 
-impl Into<HumanDuration> for chrono::Duration {
-    fn into(self) -> HumanDuration {
-        HumanDuration {
-            hours: self.num_hours(),
-            minutes: self.num_minutes() % 60
-        }
-    }
-}
+/// Add, list and remove absences
+fn absence(command: &AbsenceCommand, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let namespace = tracker.namespace(args.namespace());
+    match command {
+        AbsenceCommand::Add { date, to, kind, comment } => {
+            let added = namespace.add_absences(*date, to.unwrap_or(*date), kind.clone(), comment.clone())?;
+            println!("Adding absence for namespace '{}':", args.namespace());
+            for absence in added {
+                println!("\t {} {}", absence.date.format("%Y-%m-%d %a"), absence.kind);
+            }
+        },
+        AbsenceCommand::List { year } => {
+            let mut absences = namespace.absences()?;
+            if let Some(year) = year {
+                absences.retain(|a| a.date.year() == *year);
+            }
+            let vacation = namespace.vacation(year.unwrap_or(now().year()))?;
+            if args.output != OutputFormat::Table {
+                return print_structured(&AbsencesOutput { absences, vacation }, args.output);
+            }
 
-impl Into<TableEntry> for &Entry {
-    fn into(self) -> TableEntry {
-        TableEntry {
-            date: self.start_time.date_naive(),
-            from: self.start_time.time(),
-            to: self.end_time.time(),
-            duration: self.duration(),
-            comment: self.comment.clone().unwrap_or(String::new()),
-        }
+            if absences.is_empty() {
+                println!("No absences recorded for namespace '{}'", args.namespace());
+            } else {
+                print_entries(&absences, 0, 80, args)?;
+            }
+            match (vacation.quota, vacation.remaining) {
+                (Some(quota), Some(remaining)) => println!("Vacation {}: {} of {} days taken, {} remaining",
+                                                           vacation.year, vacation.taken, quota, remaining),
+                _ => println!("Vacation {}: {} days taken", vacation.year, vacation.taken),
+            }
+        },
+        AbsenceCommand::Remove { date, to } => {
+            let removed = namespace.remove_absences(*date, to.unwrap_or(*date))?;
+            println!("Removing absence for namespace '{}':", args.namespace());
+            for absence in removed {
+                println!("\t {} {}", absence.date.format("%Y-%m-%d %a"), absence.kind);
+            }
+        },
+        AbsenceCommand::Quota { days } => {
+            namespace.update_settings(|settings| {
+                settings.vacation_days = Some(*days);
+                Ok(())
+            })?;
+            println!("Namespace '{}' now has {} vacation days per year", args.namespace(), days);
+        },
     }
+    Ok(())
 }
 
-impl Into<SummaryTableItem> for (String, Vec<Entry>) {
-    fn into(self) -> SummaryTableItem {
-        let (month, entries) = self;
-        let mut dates: Vec<NaiveDate> = entries.iter().map(|e| e.start_time.date_naive()).collect();
-        dates.dedup();
-
-        let total_hours = entries.iter()
-            .fold(HumanDuration::zero(), |d, e| d + e.duration());
-        let days = dates.len();
-        let weeks: f32 = (days_in_month(entries.first().unwrap().start_time.date_naive()) as f32) / 7.0;
-        let rem_minutes = if total_hours.minutes == 0 { 0.0 } else { 60.0 / total_hours.minutes as f32 };
-        let hours_per_week = ((total_hours.hours as f32) + rem_minutes) / weeks;
-
-        SummaryTableItem {
-            month,
-            total_hours, days,
-            hours_per_week: format!("{:.2}", hours_per_week),
-            nitems: entries.len(),
+/// Change the start, end or comment of an entry
+fn amend(id: &str, start: Option<NaiveDateTime>, end: Option<NaiveDateTime>, comment: Option<String>,
+         tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let start = start.map(|t| local_datetime(&t)).transpose()?;
+    let end = end.map(|t| local_datetime(&t)).transpose()?;
+    let entry = tracker.namespace(args.namespace()).amend_entry(id, |entry| {
+        if let Some(start) = start {
+            entry.start_time = start;
         }
-    }
+        if let Some(end) = end {
+            entry.end_time = end;
+        }
+        if comment.is_some() {
+            entry.comment = comment;
+        }
+    })?;
+    print_entry(&format!("Amended entry in namespace '{}'", args.namespace()), &entry);
+    Ok(())
 }
 
+/// Change the details of the running clock
+fn amend_running(starting: Option<NaiveDateTime>, comment: Option<String>, remove_tags: &[String], details: DetailArgs,
+                 tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let clock = tracker.namespace(args.namespace()).amend_running(|clock| {
+        if let Some(time) = starting {
+            clock.start_time = local_datetime(&time)?;
+        }
+        if comment.is_some() {
+            clock.comment = comment;
+        }
+        clock.tags.retain(|t| !remove_tags.contains(t));
+        clock.add_tags(details.tags);
+        if details.project.is_some() {
+            clock.project = details.project;
+        }
+        if details.task.is_some() {
+            clock.task = details.task;
+        }
+        Ok(())
+    })?;
 
-/// Abort the currently running clock by deleting its file
-fn abort(args: &Args) -> Result<(), String> {
-    let path = ClockinTimestamp::relative_path(&args.namespace);
-
-    if !data_file_exists(&path).unwrap() {
-        Err(format!("Clock is not running for namespace '{}'", args.namespace))
-    } else {
-        println!("Aborting clock for namespace '{}'", args.namespace);
-        remove_data_file(&path)
-    }
+    println!("Amended clock for namespace '{}' (started {})", args.namespace(), clock.start_time);
+    Ok(())
 }
 
-fn clock(start: DateTime<Local>, end: DateTime<Local>, comment: Option<String>, args: &Args) -> Result<(), String> {
-    let path = Entry::relative_path(&args.namespace);
-    let mut entries = if data_file_exists(&path).unwrap() {
-        read_data_file(&path)?
-    } else {
-        Vec::new()
-    };
-
-    // Sort entries
-    // TODO: Implement Ord/PartialOrd to use only the starting time
-    entries.sort();
-
-    let entry = Entry::create(start, end, comment);
-
-    // check if any entry overlaps
-    if entries.iter().any(|e| entries_overlap(&entry, e)) {
-        return Err("New entry overlaps an existing one".to_owned());
+/// Print the balance of worked and expected hours
+fn balance(until: Option<NaiveDate>, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let until = until.unwrap_or(now().date_naive());
+    let balance = tracker.namespace(args.namespace()).balance(until)?;
+    if args.output != OutputFormat::Table {
+        return print_structured(&balance, args.output);
     }
 
-    println!("Adding entry for namespace '{}':", args.namespace);
-    println!("\t starting at {}", entry.start_time);
-    println!("\t ending at   {}", entry.end_time);
-    println!("\t duration:   {}", entry.duration());
-    if let Some(comment) = &entry.comment {
-        println!("\t comment:    {}", comment);
-    }
+    println!("Balance of namespace '{}' from {} to {}:", args.namespace(), balance.since, balance.until);
+    println!("\t carry-over: {:+.2}h", balance.carry_over);
+    println!("\t worked:     {:.2}h", balance.worked_hours);
+    println!("\t expected:   {:.2}h", balance.expected_hours);
+    println!("\t absent:     {} days", balance.absent_days);
+    println!("\t holidays:   {} days", balance.holidays);
+    println!("\t balance:    {:+.2}h", balance.balance);
+    Ok(())
+}
 
-    entries.push(entry);
-    write_data_file(&path, entries)
+fn clock(start: DateTime<Local>, end: DateTime<Local>, comment: Option<String>, details: DetailArgs,
+         tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let entry = Entry::create(start, end, comment, details.tags)
+        .with_project(details.project)
+        .with_task(details.task);
+    let entry = tracker.namespace(args.namespace()).clock(entry)?;
+    print_entry(&format!("Adding entry for namespace '{}'", args.namespace()), &entry);
+    if entry.end_time > now() {
+        println!("Warning: the entry ends in the future");
+    }
+    Ok(())
 }
 
-fn clock_duration(hours: f64, starting: Option<NaiveDateTime>, comment: Option<String>, args: &Args)
-        -> Result<(), String> {
-    let duration = chrono::Duration::minutes((hours * 60.0) as i64);
+fn clock_duration(hours: f64, starting: Option<NaiveDateTime>, comment: Option<String>, details: DetailArgs,
+                  tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let duration = duration_from_hours(hours)?;
 
     let (start, end) = if let Some(starting) = starting {
-        let start = Local.from_local_datetime(&starting).unwrap();
-        let end = start + duration;
+        let start = local_datetime(&starting)?;
+        let end = start.checked_add_signed(duration)
+            .ok_or(format!("Entry starting at {} can not last {} hours", start, hours))?;
         (start, end)
     } else {
         let end = now();
@@ -416,43 +1180,121 @@ fn clock_duration(hours: f64, starting: Option<NaiveDateTime>, comment: Option<S
         (start, end)
     };
 
-    clock(start, end, comment, args)
+    clock(start, end, comment, details, tracker, args)
 }
 
-/// Start a new clock by creating a new clockin file
-fn clockin(starting: Option<NaiveDateTime>, args: &Args) -> Result<(), String> {
-    let path = ClockinTimestamp::relative_path(&args.namespace);
+/// Clock the hours or time range given as `span`, or the time from `starting` to `end`
+fn clock_span(span: Option<&str>, starting: Option<NaiveDateTime>, end: Option<&str>, comment: Option<String>, details: DetailArgs,
+              tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let (start, end, comment) = match (span, end, starting) {
+        // with --end the first argument is the comment
+        (Some(_), Some(_), _) if comment.is_some() => return Err("The hours or a time range can not be combined with --end".to_owned()),
+        (span, Some(end), Some(starting)) => (starting, parse_end_time(end, starting)?, span.map(|s| s.to_owned())),
+        (Some(span), None, starting) => match span.parse::<f64>() {
+            Ok(hours) => return clock_duration(hours, starting, comment, details, tracker, args),
+            Err(_) if starting.is_some() => return Err("A time range can not be combined with --starting".to_owned()),
+            Err(_) => {
+                let (start, end) = parse_time_range(span)?;
+                (start, end, comment)
+            },
+        },
+        _ => return Err("Please give the hours, a time range (e.g. 09:00-12:30) or --start and --end".to_owned()),
+    };
+    clock(local_datetime(&start)?, local_datetime(&end)?, comment, details, tracker, args)
+}
+
+/// Start a new clock
+fn clockin(starting: Option<NaiveDateTime>, comment: Option<String>, details: DetailArgs, tracker: &Tracker, args: &Args) -> Result<(), String> {
     let timestamp = if let Some(time) = starting {
-        ClockinTimestamp::starting(&time)
+        ClockinTimestamp::starting(&time)?
     } else {
         ClockinTimestamp::now()
     };
+    let timestamp = timestamp.with_comment(comment)
+        .with_tags(details.tags)
+        .with_project(details.project)
+        .with_task(details.task);
 
-    if data_file_exists(&path).unwrap() {
-        return Err(format!("Clock-in file '{}' already exists.\nPlease remove it before continuing.", path.to_string_lossy()));
-    }
+    let timestamp = tracker.namespace(args.namespace()).clockin(timestamp)?;
+    println!("Starting clock for namespace '{}' ({})", args.namespace(), timestamp.start_time);
+    Ok(())
+}
+
+/// Stop the clock and add the entry to the entries file
+fn clockout(comment: Option<String>, at: Option<NaiveDateTime>, ago: Option<chrono::Duration>, details: DetailArgs,
+            tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let details = ClockoutDetails {
+        comment,
+        tags: details.tags,
+        project: details.project,
+        task: details.task,
+    };
+    let end_time = match (at, ago) {
+        (Some(at), _) => local_datetime(&at)?,
+        (None, Some(ago)) => now() - ago,
+        (None, None) => now(),
+    };
+    let entry = tracker.namespace(args.namespace()).clockout_at(details, end_time)?;
+    print_entry(&format!("Adding entry for namespace '{}'", args.namespace()), &entry);
+    Ok(())
+}
 
-    println!("Starting clock for namespace '{}' ({})", args.namespace, timestamp.start_time);
-    write_data_file(&path, timestamp)
+/// Get, set or list the defaults in the configuration file
+fn config(command: &ConfigCommand, args: &Args) -> Result<(), String> {
+    let mut config = args.config.clone();
+    match command {
+        ConfigCommand::Get { key } => match config.get(key)? {
+            Some(value) => println!("{}", value),
+            None => return Err(format!("'{}' is not set", key)),
+        },
+        ConfigCommand::Set { key, value } => {
+            config.set(key, value)?;
+            config.save()?;
+            println!("Set '{}' to '{}'", key, value);
+        },
+        ConfigCommand::Unset { key } => {
+            config.unset(key)?;
+            config.save()?;
+            println!("Unset '{}'", key);
+        },
+        ConfigCommand::List {} => {
+            if args.output != OutputFormat::Table {
+                return print_structured(&config, args.output);
+            }
+            for (key, value) in config.list() {
+                println!("{}: {}", key, value);
+            }
+        },
+    }
+    Ok(())
 }
 
-/// Stop the clock and entry to the entries file
-fn clockout(comment: Option<String>, args: &Args) -> Result<(), String> {
-    let clockin_path = ClockinTimestamp::relative_path(&args.namespace);
-    let clockin_timestamp: ClockinTimestamp = read_data_file(&clockin_path)
-        .map_err(|_| "No clockin file found".to_owned())?;
+/// Ask a yes/no question on the terminal
+fn confirm(question: &str, default: bool) -> Result<bool, String> {
+    print!("{} {} ", question, if default { "[Y/n]" } else { "[y/N]" });
+    io::stdout().flush()
+        .map_err(|e| e.to_string())?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)
+        .map_err(|e| e.to_string())?;
 
-    clock(clockin_timestamp.start_time, now(), comment, args)?;
-    remove_data_file(&clockin_path)
+    Ok(match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    })
 }
 
-fn days_in_month(date: NaiveDate) -> i64 {
-    let date_next_month = date.checked_add_months(Months::new(1)).unwrap();
-    date_next_month.signed_duration_since(date).num_days()
+/// Delete an entry
+fn delete(id: &str, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let entry = tracker.namespace(args.namespace()).delete_entry(id)?;
+    print_entry(&format!("Deleted entry from namespace '{}'", args.namespace()), &entry);
+    Ok(())
 }
 
-fn edit(clockin: bool, args: &Args) -> Result<(), String> {
-    let find_env = |name: &str| env::vars().into_iter()
+/// Edit entries or the running clock in a temporary file, re-opening it until the content is valid
+fn edit(clockin: bool, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let find_env = |name: &str| env::vars()
         .find(|(k, _)| k == name)
         .map(|(_, v)| v);
 
@@ -460,42 +1302,77 @@ fn edit(clockin: bool, args: &Args) -> Result<(), String> {
         .or(find_env("VISUAL"))
         .unwrap_or("vi".to_owned());
 
-    let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
-        .map_err(|e| e.to_string())?;
-    let path = if clockin {
-        let rel_path = ClockinTimestamp::relative_path(&args.namespace);
-        xdg_dirs.find_data_file(rel_path)
-            .ok_or(format!("No clockin file found for namespace '{}'", args.namespace))?
-    } else {
-        let rel_path = Entry::relative_path(&args.namespace);
-        xdg_dirs.find_data_file(rel_path)
-            .ok_or(format!("No entry file found for namespace '{}'", args.namespace))?
+    let namespace = tracker.namespace(args.namespace());
+    let path = namespace.edit_file()?;
+    let open_editor = |content: &str, error: Option<&str>| -> Result<Option<String>, String> {
+        let mut file_content = String::new();
+        if let Some(error) = error {
+            println!("Error: {}", error);
+            if !confirm("Re-open the editor?", true)? {
+                return Ok(None);
+            }
+            for line in error.lines() {
+                file_content.push_str(&format!("{}{}\n", EDIT_ERROR_PREFIX, line));
+            }
+        }
+        file_content.push_str(content);
+        write_private(&path, &file_content)?;
+
+        let status = process::Command::new(&editor)
+            .arg(&path)
+            .status()
+            .map_err(|_| "Unable to open editor")?;
+        if !status.success() {
+            return Err("Editor exited with error code".to_owned());
+        }
+
+        let edited = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())?;
+        let edited = edited.lines()
+            .skip_while(|l| l.starts_with(EDIT_ERROR_PREFIX))
+            .map(|l| format!("{}\n", l))
+            .collect();
+        Ok(Some(edited))
     };
 
-    let mut child = process::Command::new(editor)
-        .arg(path)
-        .spawn()
-        .map_err(|_| "Unable to open editor" )?;
-    child.wait()
-        .map_err(|_| "Editor exited with error code" )?;
+    let result = if clockin {
+        namespace.edit_clockin(open_editor)
+    } else {
+        namespace.edit_entries(open_editor)
+    };
+    let _ = fs::remove_file(&path);
 
+    if !result? {
+        println!("Discarded changes");
+    }
     Ok(())
 }
 
-/// Check if the timespan of two entries overlap
-fn entries_overlap(e1: &Entry, e2: &Entry) -> bool {
-    (e1.start_time > e2.start_time && e1.start_time < e2.end_time)
-        || (e1.end_time > e2.start_time && e1.end_time < e2.end_time)
-        || (e2.start_time > e1.start_time && e2.start_time < e1.end_time)
-        || (e2.end_time > e1.start_time && e2.end_time < e1.end_time)
+/// Export entries to a file or stdout
+fn export(format: ExportFormat, file: &Option<PathBuf>, delimiter: char, from: Option<NaiveDate>, to: Option<NaiveDate>,
+          tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let mut entries = tracker.namespace(args.namespace()).entries()?;
+    entries.retain(|e| from.map_or(true, |d| e.start_time.date_naive() >= d)
+        && to.map_or(true, |d| e.start_time.date_naive() <= d));
+
+    let content = match format {
+        ExportFormat::Csv => formats::csv::export(&entries, ascii_delimiter(delimiter)?)?,
+        ExportFormat::Ics => formats::ics::export(&entries, args.namespace()),
+    };
+
+    match file {
+        Some(path) => fs::write(path, content).map_err(|e| e.to_string()),
+        None => {
+            print!("{}", content);
+            Ok(())
+        },
+    }
 }
 
 /// Run git command in foliot data directory
-fn git(git_args: &Vec<String>, _args: &Args) -> Result<(), String> {
-    let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
-        .map_err(|e| e.to_string())?;
-    let working_dir = xdg_dirs.find_data_file("").ok_or(format!("Path not found"))?;
-    let path = working_dir.to_str().ok_or(format!("Unable to convert path to string"))?;
+fn git(git_args: &[String], tracker: &Tracker, _args: &Args) -> Result<(), String> {
+    let working_dir = tracker.data_dir()?;
+    let path = working_dir.to_str().ok_or("Unable to convert path to string".to_owned())?;
 
     let args: Vec<&str> = iter::once("-C").chain(iter::once(path))
         .chain(git_args.iter().map(|s| s as &str))
@@ -511,117 +1388,205 @@ fn git(git_args: &Vec<String>, _args: &Args) -> Result<(), String> {
     Ok(())
 }
 
-/// Check whether a file with the relative path `path` exists in the data directory
-fn data_file_exists(path: &impl AsRef<Path>) -> Result<bool, String> {
-    let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
-        .map_err(|e| e.to_string())?;
-    Ok(xdg_dirs.find_data_file(path).is_some())
-}
-
-/// Return current time in the current timezone
-fn now() -> DateTime<Local> {
-    Local::now().duration_round(chrono::Duration::minutes(1)).unwrap()
-}
-
-/// Parse a starting value
-fn parse_starting_value(s: &str) -> Result<NaiveDateTime, String> {
-    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
-        .or(NaiveDateTime::parse_from_str(s, "%d.%m.%Y-%H:%M"))
-        .or(NaiveDateTime::parse_from_str(s, "%d.%m.%Y %H:%M"))
-        .or(parse_starting_value_time(s))
-        .map_err(|_| format!("unable to parse datetime '{}'", s))
+/// Select, unselect or list the public holidays of the namespace
+fn holidays(command: &HolidaysCommand, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let namespace = tracker.namespace(args.namespace());
+    match command {
+        HolidaysCommand::List { year } => {
+            let calendar = namespace.settings()?.holidays
+                .ok_or(format!("No holidays selected for namespace '{}'", args.namespace()))?;
+            let year = year.unwrap_or(now().year());
+            NaiveDate::from_ymd_opt(year, 1, 1)
+                .ok_or(format!("Year {} is out of range", year))?;
+            let holidays = calendar.holidays(year);
+            print_entries(&holidays, 0, 80, args)?;
+        },
+        HolidaysCommand::Set { calendar } => {
+            namespace.update_settings(|settings| {
+                settings.holidays = Some(calendar.clone());
+                Ok(())
+            })?;
+            println!("Namespace '{}' now observes the public holidays of {}", args.namespace(), calendar);
+        },
+        HolidaysCommand::Unset {} => {
+            namespace.update_settings(|settings| {
+                settings.holidays = None;
+                Ok(())
+            })?;
+            println!("Namespace '{}' no longer observes public holidays", args.namespace());
+        },
+    }
+    Ok(())
 }
 
-/// Parse a starting datetime based on the time alone (either today or yesterday)
-fn parse_starting_value_time(s: &str) -> Result<NaiveDateTime, String> {
-    let time = NaiveTime::parse_from_str(s, "%H:%M")
-        .or(NaiveTime::parse_from_str(s, "%H:%Mh"))
-        .or(NaiveTime::parse_from_str(s, "%H%M"))
-        .or(NaiveTime::parse_from_str(s, "%H%Mh"))
-        .map_err(|_| format!("unable to parse time '{}'", s))?;
-
-    let current_datetime = now();
-    let date = if current_datetime.time() > time {
-        current_datetime.date_naive()
-    } else {
-        current_datetime.date_naive()
-            .checked_sub_days(chrono::Days::new(1)).unwrap()
+/// Import entries from a file, skipping the ones that can not be added
+fn import(format: ImportFormat, file: &Path, delimiter: char, dry_run: bool, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let content = fs::read_to_string(file)
+        .map_err(|e| e.to_string())?;
+    let rows = match format {
+        ImportFormat::Csv => formats::csv::parse(&content, ascii_delimiter(delimiter)?)?,
+        ImportFormat::Timewarrior => formats::timewarrior::parse(&content)?,
+        ImportFormat::Watson => formats::watson::parse(&content)?,
+        ImportFormat::TogglCsv => formats::toggl::parse(&content)?,
     };
 
-    Ok(NaiveDateTime::new(date, time))
-}
+    let mut rejected = 0;
+    let mut parsed = Vec::new();
+    for (line, row) in rows {
+        match row {
+            Ok(entry) => parsed.push((line, entry)),
+            Err(e) => {
+                println!("Rejected row {}: {}", line, e);
+                rejected += 1;
+            },
+        }
+    }
 
-/// Print path to foliot data
-fn print_path(namespace: Option<String>, _args: &Args) -> Result<(), String> {
-    let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
-        .map_err(|e| e.to_string())?;
+    let namespace = tracker.namespace(args.namespace());
+    let outcomes = namespace.import(parsed.iter().map(|(_, e)| e.clone()).collect(), dry_run)?;
+    let mut imported = Vec::new();
+    let mut duplicates = 0;
+    for ((line, entry), outcome) in parsed.into_iter().zip(outcomes) {
+        match outcome {
+            ImportOutcome::Added => imported.push(entry),
+            ImportOutcome::Duplicate => duplicates += 1,
+            ImportOutcome::Invalid(e) => {
+                println!("Rejected row {}: {}", line, e);
+                rejected += 1;
+            },
+            ImportOutcome::Overlapping => {
+                println!("Rejected row {}: overlaps an existing entry", line);
+                rejected += 1;
+            },
+        }
+    }
 
-    let path = if let Some(namespace) = namespace {
-        let rel_path = Entry::relative_path(&namespace);
-        let working_dir = xdg_dirs.find_data_file(rel_path).ok_or(format!("Path not found"))?;
-        working_dir.to_str().ok_or(format!("Unable to convert path to string"))?
-            .to_owned()
+    if dry_run {
+        let table_entries: Vec<TableEntry> = imported.iter().map(TableEntry::from).collect();
+        println!("{}", Table::new(table_entries).with(Style::rounded()));
+        println!("Would import {} entries into namespace '{}' ({} duplicates, {} rejected)",
+                 imported.len(), args.namespace(), duplicates, rejected);
     } else {
-        let working_dir = xdg_dirs.find_data_file("").ok_or(format!("Path not found"))?;
-        working_dir.to_str().ok_or(format!("Unable to convert path to string"))?
-            .to_owned()
-    };
+        println!("Imported {} entries into namespace '{}' ({} duplicates, {} rejected)",
+                 imported.len(), args.namespace(), duplicates, rejected);
+    }
+    Ok(())
+}
 
-    println!("{}", path);
+/// Join two entries into one
+fn join(id: &str, other_id: &str, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let entry = tracker.namespace(args.namespace()).join_entries(id, other_id)?;
+    print_entry(&format!("Joined entries in namespace '{}'", args.namespace()), &entry);
+    Ok(())
+}
 
+/// Move all data to another storage backend
+fn migrate(to: StorageKind, tracker: &mut Tracker, _args: &Args) -> Result<(), String> {
+    let namespaces = tracker.migrate(to)?;
+    for namespace in &namespaces {
+        println!("Migrated namespace '{}'", namespace);
+    }
+    println!("Data is now stored as {}", to);
     Ok(())
 }
 
-/// Deserialize a file with the relative path `path` in the data directory
-fn read_data_file<T: for<'a> Deserialize<'a>>(path: &impl AsRef<Path>) -> Result<T, String> {
-    let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
-        .map_err(|e| e.to_string())?;
-    let abs_path = xdg_dirs.find_data_file(path)
-        .ok_or(format!("Path not found"))?;
-    let content = fs::read(abs_path)
-        .map_err(|e| e.to_string())?;
-    serde_yaml::from_slice(&content)
-        .map_err(|e| e.to_string())
+/// List, inspect and manage namespaces
+fn namespace(command: &NamespaceCommand, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    match command {
+        NamespaceCommand::List {} => {
+            let namespaces = tracker.namespaces()?;
+            if args.output != OutputFormat::Table {
+                return print_structured(&namespaces, args.output);
+            }
+            for namespace in namespaces {
+                println!("{}", namespace);
+            }
+        },
+        NamespaceCommand::Info { name } => {
+            let info = tracker.namespace(name.as_deref().unwrap_or(args.namespace())).info()?;
+            if args.output != OutputFormat::Table {
+                return print_structured(&info, args.output);
+            }
+            let display_time = |t: Option<DateTime<Local>>| t.map(|t| t.to_string()).unwrap_or("-".to_owned());
+            println!("Namespace '{}':", info.namespace);
+            println!("\t entries:     {}", info.entries);
+            println!("\t first entry: {}", display_time(info.first_entry));
+            println!("\t last entry:  {}", display_time(info.last_entry));
+            println!("\t clock:       {}", if info.running { "running" } else { "stopped" });
+        },
+        NamespaceCommand::Rename { from, to } => {
+            tracker.namespace(from).rename(to)?;
+            println!("Renamed namespace '{}' to '{}'", from, to);
+        },
+        NamespaceCommand::Merge { from, into, on_overlap } => {
+            let summary = tracker.namespace(from).merge_into(into, *on_overlap)?;
+            println!("Merged {} entries of namespace '{}' into '{}' ({} overlapping entries dropped)",
+                     summary.merged, from, into, summary.dropped);
+        },
+        NamespaceCommand::Delete { name, yes } => {
+            let namespace = tracker.namespace(name);
+            let info = namespace.info()?;
+            if !yes && !confirm(&format!("Delete namespace '{}' with {} entries?", name, info.entries), false)? {
+                println!("Aborted");
+                return Ok(());
+            }
+            namespace.delete()?;
+            println!("Deleted namespace '{}'", name);
+        },
+    }
+    Ok(())
 }
 
-/// Delete a file with the relative path `path` in the data directory
-fn remove_data_file(path: &impl AsRef<Path>) -> Result<(), String> {
-    let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
-        .map_err(|e| e.to_string())?;
-    let abs_path = xdg_dirs.find_data_file(path)
-        .ok_or(format!("Path not found"))?;
-    fs::remove_file(abs_path)
-        .map_err(|e| e.to_string())
+/// Print a newly added entry
+fn print_entry(heading: &str, entry: &Entry) {
+    println!("{}:", heading);
+    println!("\t id:         {}", entry.short_id());
+    println!("\t starting at {}", entry.start_time);
+    println!("\t ending at   {}", entry.end_time);
+    println!("\t duration:   {}", entry.duration());
+    if let Some(project) = &entry.project {
+        println!("\t project:    {}", project);
+    }
+    if let Some(task) = &entry.task {
+        println!("\t task:       {}", task);
+    }
+    if !entry.tags.is_empty() {
+        println!("\t tags:       {}", entry.tags.join(", "));
+    }
+    if let Some(comment) = &entry.comment {
+        println!("\t comment:    {}", comment);
+    }
 }
 
-/// Print human readable table to the terminal
-fn show(filter: &Option<String>, tail: usize, wrap: usize, args: &Args) -> Result<(), String> {
-    let path = Entry::relative_path(&args.namespace);
-    let mut entries: Vec<Entry> = if data_file_exists(&path).unwrap() {
-        read_data_file(&path)?
+/// Convert a delimiter given on the command line to a single byte
+fn ascii_delimiter(delimiter: char) -> Result<u8, String> {
+    if delimiter.is_ascii() {
+        Ok(delimiter as u8)
     } else {
-        return Err(format!("No file found for namespace '{}'", args.namespace));
-    };
-    entries.sort();
-
-    let regex_opt = match filter {
-        Some(filter_str) => Some(Regex::new(filter_str).map_err(|e| e.to_string())?),
-        None => None,
-    };
+        Err(format!("Delimiter '{}' is not an ASCII character", delimiter))
+    }
+}
 
-    let table_entries: Vec<TableEntry> = entries.iter()
-        .filter(|&e| regex_opt.as_ref().map_or(true, |re| e.comment.as_ref().map_or(true, |c| re.is_match(&c))))
-        .map(|e| e.into())
-        .collect();
+/// Pause the running clock
+fn pause(tracker: &Tracker, args: &Args) -> Result<(), String> {
+    tracker.namespace(args.namespace()).pause()?;
+    println!("Pausing clock for namespace '{}'", args.namespace());
+    Ok(())
+}
 
+/// Print the last `tail` entries as table or in a machine-readable format
+fn print_entries<T: Tabled + Serialize>(table_entries: &[T], tail: usize, wrap: usize, args: &Args) -> Result<(), String> {
+    let idx = table_entries.len().saturating_sub(tail);
     let entry_slice = if tail == 0 {
-        table_entries.as_slice()
+        table_entries
     } else {
-        let len = table_entries.len();
-        let idx = if len > tail { len - tail } else { 0 };
-        &table_entries.as_slice()[idx..]
+        &table_entries[idx..]
     };
 
+    if args.output != OutputFormat::Table {
+        return print_structured(&entry_slice, args.output);
+    }
+
     let table = Table::new(entry_slice)
         .with(Style::rounded())
         .with(Rows::new(1..).not(Columns::first()).not(Columns::last()).modify().with(Alignment::center()))
@@ -634,134 +1599,368 @@ fn show(filter: &Option<String>, tail: usize, wrap: usize, args: &Args) -> Resul
     Ok(())
 }
 
-fn status(args: &Args) -> Result<(), String> {
-    let path = ClockinTimestamp::relative_path(&args.namespace);
+/// Print data in a machine-readable format
+fn print_structured(data: &impl Serialize, format: OutputFormat) -> Result<(), String> {
+    let output = match format {
+        OutputFormat::Json => serde_json::to_string_pretty(data).map_err(|e| e.to_string())?,
+        OutputFormat::Yaml => serde_yaml::to_string(data).map_err(|e| e.to_string())?,
+        OutputFormat::Table => return Err("Data can not be printed as table".to_owned()),
+    };
+    println!("{}", output.trim_end());
+    Ok(())
+}
 
-    if !data_file_exists(&path).unwrap() {
-        println!("Clock is not running for namespace '{}'", args.namespace);
-    } else {
-        let clockin_timestamp: ClockinTimestamp = read_data_file(&path)?;
-        let duration: HumanDuration = (now() - clockin_timestamp.start_time).into();
-        println!("Clock running for namespace '{}':", args.namespace);
-        println!("\t started {}", clockin_timestamp.start_time);
-        println!("\t running {}", duration);
+/// Print summary rows as table, naming the column of the groups after `by`
+///
+/// Columns with a header in `hidden` are left out.
+fn print_summary<T: Tabled>(rows: Vec<T>, group_column: usize, by: Grouping, hidden: &[&str]) {
+    let mut table = Table::new(rows);
+    table.with(Style::rounded())
+        .with(Rows::new(1..).not(Columns::new(..=group_column)).modify().with(Alignment::center()))
+        .with(Modify::new(Cell(0, group_column)).with(by.to_string()));
+    // remove from the back, so the indices of the remaining columns stay valid
+    for (column, _) in T::headers().iter().enumerate().rev().filter(|(_, h)| hidden.contains(&h.as_ref())) {
+        table.with(Disable::column(Columns::single(column)));
     }
+    let table = table
+        .with(Color::FG_GREEN)
+        .with(Margin::new(1, 1, 1, 1))
+        .to_string();
+    println!("{}", table);
+}
 
-    Ok(())
+/// Print the expected hours of a namespace
+fn print_target(namespace: &str, target: &Target) {
+    let weekdays = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let hours: Vec<String> = weekdays.iter().zip(target.hours_per_weekday)
+        .map(|(day, hours)| format!("{} {:.2}", day, hours))
+        .collect();
+    println!("Target of namespace '{}':", namespace);
+    println!("\t since:      {}", target.since);
+    println!("\t per week:   {:.2}h", target.hours_per_week());
+    println!("\t per day:    {}", hours.join(", "));
+    println!("\t carry-over: {:+.2}h", target.carry_over);
+}
+
+/// Headers of the summary columns none of `items` has a value for
+fn empty_summary_columns<'a>(items: impl Iterator<Item = &'a SummaryTableItem> + Clone) -> Vec<&'static str> {
+    let mut empty = Vec::new();
+    if items.clone().all(|i| i.balance.is_none()) {
+        empty.extend(["expected", "balance"]);
+    }
+    if items.clone().all(|i| i.holidays.is_empty()) {
+        empty.push("holidays");
+    }
+    empty
 }
 
-fn summarize(filter: &Option<String>, tail: usize, args: &Args) -> Result<(), String> {
-    let path = Entry::relative_path(&args.namespace);
-    let mut entries: Vec<Entry> = if data_file_exists(&path).unwrap() {
-        read_data_file(&path)?
+/// Print path to foliot data
+fn print_path(namespace: Option<String>, tracker: &Tracker, _args: &Args) -> Result<(), String> {
+    let path = if let Some(namespace) = namespace {
+        tracker.namespace(&namespace).entries_file()
+            .map_err(|_| "Path not found".to_owned())?
     } else {
-        return Err(format!("No file found for namespace '{}'", args.namespace));
+        tracker.data_dir()?
     };
-    entries.sort();
 
-    let regex_opt = match filter {
-        Some(filter_str) => Some(Regex::new(filter_str).map_err(|e| e.to_string())?),
-        None => None,
-    };
+    let path = path.to_str().ok_or("Unable to convert path to string".to_owned())?;
+    println!("{}", path);
 
-    if let Some(re) = regex_opt {
-        entries = entries.into_iter()
-            .filter(|e| e.comment.as_ref().map_or(true, |c| re.is_match(&c)))
-            .collect();
+    Ok(())
+}
+
+/// Resume the paused clock
+fn resume(tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let clock = tracker.namespace(args.namespace()).resume()?;
+    println!("Resuming clock for namespace '{}' (net time so far {})", args.namespace(), clock.net_elapsed());
+    Ok(())
+}
+
+/// Print human readable table to the terminal
+fn show(filter: &FilterArgs, tail: usize, wrap: usize, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    if args.multiple_namespaces() {
+        let mut table_entries = Vec::new();
+        for namespace in args.selected_namespaces(tracker)? {
+            let entries = filter.apply(tracker.storage().entries(&namespace)?.unwrap_or_default())?;
+            table_entries.extend(entries.iter().map(|e| NamespacedTableEntry {
+                namespace: namespace.clone(),
+                entry: e.into(),
+            }));
+        }
+        table_entries.sort_by_key(|e| e.entry.start_time);
+        return print_entries(&table_entries, tail, wrap, args);
     }
 
-    let mut entries_by_month: HashMap<String, Vec<Entry>> = HashMap::new();
+    let entries = filter.apply(tracker.namespace(args.namespace()).entries()?)?;
+    let table_entries: Vec<TableEntry> = entries.iter()
+        .map(|e| e.into())
+        .collect();
+    print_entries(&table_entries, tail, wrap, args)
+}
+
+/// Split an entry into two at a given time
+fn split(id: &str, at: NaiveDateTime, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let (first, second) = tracker.namespace(args.namespace()).split_entry(id, local_datetime(&at)?)?;
+    print_entry(&format!("Split entry in namespace '{}'", args.namespace()), &first);
+    print_entry("New entry", &second);
+    Ok(())
+}
 
-    for entry in entries {
-        // for now adding the month number insures correct sorting
-        let month = entry.start_time.format("%Y/%m %B").to_string();
+fn status(tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let namespaces = if args.multiple_namespaces() {
+        args.selected_namespaces(tracker)?
+    } else {
+        vec![args.namespace().to_owned()]
+    };
+    let statuses = namespaces.iter()
+        .map(|n| tracker.namespace(n).status())
+        .collect::<Result<Vec<_>, _>>()?;
 
-        if let Some(month_vec) = entries_by_month.get_mut(&month) {
-            month_vec.push(entry);
+    if args.output != OutputFormat::Table {
+        return if args.multiple_namespaces() {
+            print_structured(&statuses, args.output)
         } else {
-            let month_vec = vec![entry];
-            entries_by_month.insert(month, month_vec);
+            print_structured(&statuses[0], args.output)
+        };
+    }
+
+    for status in statuses {
+        match (status.start_time, status.elapsed, status.net_elapsed) {
+            (Some(start_time), Some(elapsed), Some(net_elapsed)) => {
+                println!("Clock running for namespace '{}':", status.namespace);
+                println!("\t started {}", start_time);
+                println!("\t running {} ({} without breaks)", elapsed, net_elapsed);
+                if let Some(paused_since) = status.paused_since {
+                    println!("\t paused since {}", paused_since);
+                }
+                if let Some(comment) = &status.comment {
+                    println!("\t comment {}", comment);
+                }
+            },
+            _ => println!("Clock is not running for namespace '{}'", status.namespace),
         }
     }
 
-    let mut table_items: Vec<SummaryTableItem> = entries_by_month.drain()
-        .map(|m| m.into())
-        .collect();
-    table_items.sort();
+    Ok(())
+}
 
-    let mut tailed_items = if tail == 0 {
-        table_items
+fn summarize(filter: &FilterArgs, by: Grouping, partial: bool, tail: usize, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let tail_items = |items: Vec<SummaryTableItem>| if tail == 0 {
+        items
     } else {
-        let len = table_items.len();
-        let idx = if len > tail { len - tail } else { 0 };
-        table_items[idx..].to_vec()
+        let idx = items.len().saturating_sub(tail);
+        items[idx..].to_vec()
     };
 
-    let total = SummaryTableItem {
-        month: "Total".to_owned(),
-        total_hours: tailed_items.iter().map(|i| i.total_hours)
-            .fold(HumanDuration { hours: 0, minutes: 0 }, |a, b| a + b),
-        hours_per_week: "-".to_owned(),
-        days: tailed_items.iter().map(|i| i.days).sum(),
-        nitems: tailed_items.iter().map(|i| i.nitems).sum(),
-    };
+    if args.multiple_namespaces() {
+        let mut summaries = Vec::new();
+        for namespace in args.selected_namespaces(tracker)? {
+            let entries = filter.apply(tracker.storage().entries(&namespace)?.unwrap_or_default())?;
+            let schedule = filter.schedule(tracker.namespace(&namespace).schedule()?);
+            let groups = tail_items(foliot::summarize(entries, by, partial, &schedule));
+            if !groups.is_empty() {
+                let total = SummaryTableItem::total(&groups);
+                summaries.push(NamespaceSummary { namespace, groups, total });
+            }
+        }
+        let subtotals: Vec<SummaryTableItem> = summaries.iter().map(|s| s.total.clone()).collect();
+        let balances: Vec<f64> = subtotals.iter().filter_map(|s| s.balance).collect();
+        let total = SummaryTableItem {
+            balance: (!balances.is_empty()).then(|| balances.iter().sum()),
+            ..SummaryTableItem::total(&subtotals)
+        };
+
+        if args.output != OutputFormat::Table {
+            let output = NamespacesSummaryOutput { grouping: by.to_string(), namespaces: summaries, total };
+            return print_structured(&output, args.output);
+        }
+
+        let mut rows = Vec::new();
+        for summary in summaries {
+            for item in summary.groups {
+                rows.push(NamespacedSummaryItem { namespace: summary.namespace.clone(), item });
+            }
+            let subtotal = SummaryTableItem { group: "Subtotal".to_owned(), ..summary.total };
+            rows.push(NamespacedSummaryItem { namespace: summary.namespace, item: subtotal });
+        }
+        rows.push(NamespacedSummaryItem { namespace: String::new(), item: total });
+        let hidden = empty_summary_columns(rows.iter().map(|r| &r.item));
+        print_summary(rows, 1, by, &hidden);
+        return Ok(());
+    }
+
+    let namespace = tracker.namespace(args.namespace());
+    let entries = filter.apply(namespace.entries()?)?;
+    let schedule = filter.schedule(namespace.schedule()?);
+    let mut tailed_items = tail_items(foliot::summarize(entries, by, partial, &schedule));
 
+    let total = SummaryTableItem::total(&tailed_items);
+    if args.output != OutputFormat::Table {
+        let output = SummaryOutput { grouping: by.to_string(), groups: tailed_items, total };
+        return print_structured(&output, args.output);
+    }
     tailed_items.push(total);
+    let hidden = empty_summary_columns(tailed_items.iter());
+    print_summary(tailed_items, 0, by, &hidden);
 
-    let table = Table::new(tailed_items)
-        .with(Style::rounded())
-        .with(Rows::new(1..).not(Columns::first()).modify().with(Alignment::center()))
-        .with(Color::FG_GREEN)
-        .with(Margin::new(1, 1, 1, 1))
-        .to_string();
-    println!("{}", table);
+    Ok(())
+}
+
+/// Stop the running clock and start one for `target` at the same instant
+///
+/// The clock of `--namespace` is stopped if it is running, otherwise the only running one.
+fn switch(target: &str, comment: Option<String>, details: DetailArgs, tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let running = tracker.running_namespaces()?;
+    let source = if running.iter().any(|n| n == args.namespace()) {
+        args.namespace().to_owned()
+    } else {
+        match running.as_slice() {
+            [] => return Err("Clock is not running for any namespace".to_owned()),
+            [source] => source.clone(),
+            _ => return Err(format!(
+                "Clocks are running for multiple namespaces ({}).\nPlease select one with --namespace.",
+                running.join(", ")
+            )),
+        }
+    };
 
+    let next = ClockinTimestamp::now()
+        .with_tags(details.tags)
+        .with_project(details.project)
+        .with_task(details.task);
+    let details = ClockoutDetails { comment, ..Default::default() };
+    let (entry, next) = tracker.namespace(&source).switch_to(target, details, next)?;
+    print_entry(&format!("Adding entry for namespace '{}'", source), &entry);
+    println!("Starting clock for namespace '{}' ({})", target, next.start_time);
     Ok(())
 }
 
-/// Serialize a file with the relative path `path` in the data directory
-fn write_data_file(path: &impl AsRef<Path>, data: impl Serialize) -> Result<(), String> {
-    let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
-        .map_err(|e| e.to_string())?;
-    let abs_path = xdg_dirs.place_data_file(path)
-        .map_err(|e| e.to_string())?;
-    let content = serde_yaml::to_string(&data)
-        .map_err(|e| e.to_string())?;
-    fs::write(abs_path, content)
+/// Set, remove or show the target of the namespace
+fn target(weekly: Option<f64>, weekdays: &[f64], since: Option<NaiveDate>, carry_over: Option<f64>, remove: bool,
+          tracker: &Tracker, args: &Args) -> Result<(), String> {
+    let namespace = tracker.namespace(args.namespace());
+    if weekly.is_none() && weekdays.is_empty() && since.is_none() && carry_over.is_none() && !remove {
+        let target = namespace.settings()?.target;
+        if args.output != OutputFormat::Table {
+            return print_structured(&target, args.output);
+        }
+        return match target {
+            Some(target) => {
+                print_target(args.namespace(), &target);
+                Ok(())
+            },
+            None => Err(format!("No target set for namespace '{}'", args.namespace())),
+        };
+    }
+
+    let settings = namespace.update_settings(|settings| {
+        let previous = settings.target.take();
+        if remove {
+            return Ok(());
+        }
+
+        let since = since.or(previous.as_ref().map(|t| t.since)).unwrap_or(now().date_naive());
+        let carry_over = carry_over.or(previous.as_ref().map(|t| t.carry_over)).unwrap_or_default();
+        let hours_per_weekday: [f64; 7] = match (weekly, weekdays) {
+            (Some(hours), _) => Target::weekly(hours, since).hours_per_weekday,
+            (None, []) => previous.map(|t| t.hours_per_weekday)
+                .ok_or(format!("No target set for namespace '{}'.\nPlease give the hours with --weekly or --weekdays.", args.namespace()))?,
+            (None, weekdays) => weekdays.try_into()
+                .map_err(|_| format!("Expected 7 hours for --weekdays (Monday to Sunday), got {}", weekdays.len()))?,
+        };
+        if hours_per_weekday.iter().any(|h| !h.is_finite() || *h < 0.0) {
+            return Err("Hours must not be negative".to_owned());
+        }
+
+        settings.target = Some(Target::per_weekday(hours_per_weekday, since).with_carry_over(carry_over));
+        Ok(())
+    })?;
+
+    match settings.target {
+        Some(target) => print_target(args.namespace(), &target),
+        None => println!("Removed target of namespace '{}'", args.namespace()),
+    }
+    Ok(())
+}
+
+/// Write `content` to a new file at `path` that only the current user can read
+///
+/// A file left at `path` is replaced, without following it if it is a symlink. Other platforms
+/// than Unix keep their default permissions.
+fn write_private(path: &Path, content: &str) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.to_string()),
+        _ => {},
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options.open(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
         .map_err(|e| e.to_string())
 }
 
 fn main() {
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    args.config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("Error: {}", e);
+            std::process::exit(1);
+        },
+    };
+    if args.namespaces.is_empty() {
+        let namespace = args.config.namespace.clone().unwrap_or(DEFAULT_NAMESPACE.to_owned());
+        args.namespaces.push(namespace);
+    }
 
-    if args.namespace.is_empty() {
+    if args.namespaces.iter().any(|n| n.is_empty()) {
         println!("The namespace parameter must not be empty");
         std::process::exit(1);
     }
 
+    if args.multiple_namespaces() && !args.command.supports_multiple_namespaces() {
+        println!("Multiple namespaces are only supported by show, summarize and status");
+        std::process::exit(1);
+    }
+
+    let single_clock = match env::var(SINGLE_CLOCK_VAR) {
+        Ok(v) => v == "1" || v == "true",
+        Err(_) => args.config.single_clock.unwrap_or(false),
+    };
+    let mut tracker = match Tracker::new() {
+        Ok(tracker) => tracker.with_single_clock(single_clock),
+        Err(e) => {
+            println!("Error: {}", e);
+            std::process::exit(1);
+        },
+    };
+
     let command = args.command.clone();
-    if let Err(e) = command.execute(&args) {
+    if let Err(e) = command.execute(&mut tracker, &args) {
         println!("Error: {}", e);
         std::process::exit(1);
     }
 
-    if args.git_commit {
-        let message = format!("[{}] {}", args.namespace, command);
+    if args.git_commit() {
+        let message = format!("[{}] {}", args.namespace(), command);
         println!("\n=> git commit -am \"{}\"", message);
-        if let Err(e) = git(&vec!["commit".to_owned(), "-am".to_owned(), message], &args) {
+        if let Err(e) = git(&["commit".to_owned(), "-am".to_owned(), message], &tracker, &args) {
             println!("Error: {}", e);
             std::process::exit(1);
         }
 
-        if args.git_push {
+        if args.git_push() {
             println!("\n=> git pull --rebase");
-            if let Err(e) = git(&vec!["pull".to_owned(), "--rebase".to_owned()], &args) {
+            if let Err(e) = git(&["pull".to_owned(), "--rebase".to_owned()], &tracker, &args) {
                 println!("Error: {}", e);
                 std::process::exit(1);
             }
 
             println!("\n=> git push");
-            if let Err(e) = git(&vec!["push".to_owned()], &args) {
+            if let Err(e) = git(&["push".to_owned()], &tracker, &args) {
                 println!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -0,0 +1,90 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use crate::absence::Absence;
+use crate::holidays::{Holiday, HolidayCalendar};
+use crate::settings::Target;
+
+/// Days a namespace is expected to work on, according to its target, absences and public holidays
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Schedule {
+    pub target: Option<Target>,
+    absences: HashSet<NaiveDate>,
+    calendar: Option<HolidayCalendar>,
+
+    /// Holidays of the years looked up so far
+    holidays: RefCell<HashMap<i32, Vec<Holiday>>>,
+}
+
+impl Schedule {
+    pub fn new(target: Option<Target>) -> Self {
+        Schedule { target, ..Default::default() }
+    }
+
+    /// Do not expect any work on the days of `absences`
+    pub fn with_absences(mut self, absences: &[Absence]) -> Self {
+        self.absences.extend(absences.iter().map(|a| a.date));
+        self
+    }
+
+    /// Do not expect any work on the public holidays of `calendar`
+    pub fn with_holidays(mut self, calendar: Option<HolidayCalendar>) -> Self {
+        self.calendar = calendar;
+        self.holidays.borrow_mut().clear();
+        self
+    }
+
+    /// Whether an absence is recorded for `date`
+    pub fn is_absent(&self, date: NaiveDate) -> bool {
+        self.absences.contains(&date)
+    }
+
+    /// The public holiday on `date`, if any
+    pub fn holiday(&self, date: NaiveDate) -> Option<Holiday> {
+        let calendar = self.calendar.as_ref()?;
+        self.holidays.borrow_mut()
+            .entry(date.year())
+            .or_insert_with(|| calendar.holidays(date.year()))
+            .iter()
+            .find(|h| h.date == date)
+            .cloned()
+    }
+
+    /// Public holidays from `from` up to and including `to`
+    pub fn holidays_between(&self, from: NaiveDate, to: NaiveDate) -> Vec<Holiday> {
+        days(from, to).filter_map(|d| self.holiday(d)).collect()
+    }
+
+    /// Whether work is expected on the weekday of `date` (Monday to Friday without a target) and it is no holiday
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        let working_weekday = match &self.target {
+            Some(target) => target.is_working_day(date.weekday()),
+            None => !matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+        };
+        working_weekday && self.holiday(date).is_none()
+    }
+
+    /// Number of working days from `from` up to and including `to`
+    pub fn working_days_between(&self, from: NaiveDate, to: NaiveDate) -> usize {
+        days(from, to).filter(|d| self.is_working_day(*d)).count()
+    }
+
+    /// Hours expected to be worked on `date` (none without a target)
+    pub fn expected_hours(&self, date: NaiveDate) -> f64 {
+        match &self.target {
+            Some(target) if !self.is_absent(date) && self.holiday(date).is_none() => target.expected_hours(date),
+            _ => 0.0,
+        }
+    }
+
+    /// Hours expected to be worked from `from` up to and including `to`
+    pub fn expected_hours_between(&self, from: NaiveDate, to: NaiveDate) -> f64 {
+        days(from, to).map(|d| self.expected_hours(d)).sum()
+    }
+}
+
+/// All days from `from` up to and including `to`
+fn days(from: NaiveDate, to: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    from.iter_days().take_while(move |d| *d <= to)
+}
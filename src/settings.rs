@@ -0,0 +1,77 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Serialize, Deserialize};
+
+use crate::holidays::HolidayCalendar;
+
+/// Number of working days a weekly target is spread over (Monday to Friday)
+const WORKING_DAYS: usize = 5;
+
+/// Settings that apply to a single namespace
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamespaceSettings {
+    /// Working hours the namespace is expected to reach
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<Target>,
+
+    /// Number of vacation days per year
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vacation_days: Option<u32>,
+
+    /// Public holidays on which no work is expected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub holidays: Option<HolidayCalendar>,
+}
+
+/// Expected working hours, used to compute the balance of worked and expected hours
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Target {
+    /// Expected hours per weekday, starting with Monday
+    pub hours_per_weekday: [f64; 7],
+
+    /// First day the target applies to
+    pub since: NaiveDate,
+
+    /// Balance in hours from before `since` (negative if behind)
+    #[serde(default)]
+    pub carry_over: f64,
+}
+
+impl Target {
+    /// Target with the given hours per weekday, starting with Monday
+    pub fn per_weekday(hours_per_weekday: [f64; 7], since: NaiveDate) -> Self {
+        Target { hours_per_weekday, since, carry_over: 0.0 }
+    }
+
+    /// Target of `hours` per week, spread evenly over Monday to Friday
+    pub fn weekly(hours: f64, since: NaiveDate) -> Self {
+        let mut hours_per_weekday = [0.0; 7];
+        for day in hours_per_weekday.iter_mut().take(WORKING_DAYS) {
+            *day = hours / WORKING_DAYS as f64;
+        }
+        Self::per_weekday(hours_per_weekday, since)
+    }
+
+    /// Set the balance carried over from before the target applies
+    pub fn with_carry_over(mut self, carry_over: f64) -> Self {
+        self.carry_over = carry_over;
+        self
+    }
+
+    /// Expected hours of a full week
+    pub fn hours_per_week(&self) -> f64 {
+        self.hours_per_weekday.iter().sum()
+    }
+
+    /// Whether any hours are expected on `weekday`
+    pub fn is_working_day(&self, weekday: Weekday) -> bool {
+        self.hours_per_weekday[weekday.num_days_from_monday() as usize] > 0.0
+    }
+
+    /// Hours expected to be worked on `date` (none before the target applies)
+    pub fn expected_hours(&self, date: NaiveDate) -> f64 {
+        if date < self.since {
+            return 0.0;
+        }
+        self.hours_per_weekday[date.weekday().num_days_from_monday() as usize]
+    }
+}
@@ -0,0 +1,79 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::*;
+
+use super::XDG_DIR_PREFIX;
+
+/// Exclusive access to a namespace across processes, released when dropped
+pub struct NamespaceLock {
+    _file: Option<File>,
+}
+
+impl NamespaceLock {
+    /// A lock that does not guard anything (for backends without concurrent access)
+    pub fn none() -> Self {
+        NamespaceLock { _file: None }
+    }
+
+    /// Take an advisory lock on a namespace, failing if another process holds it
+    ///
+    /// The lock files live in the XDG cache directory, so they do not end up in the data repository.
+    pub fn acquire(namespace: &str) -> Result<Self, String> {
+        let path = cache_file(PathBuf::from(namespace).with_extension("lock"))?;
+        let file = File::create(&path)
+            .map_err(|e| e.to_string())?;
+
+        // the lock is released when the file is closed
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+            return Ok(NamespaceLock { _file: Some(file) });
+        }
+        match io::Error::last_os_error() {
+            e if e.kind() == io::ErrorKind::WouldBlock => Err(format!(
+                "Namespace '{}' is locked by another foliot process.\nPlease try again once it has finished.",
+                namespace
+            )),
+            e => Err(e.to_string()),
+        }
+    }
+}
+
+/// Path to a file in the XDG cache directory, creating the directory if needed
+pub(crate) fn cache_file(name: PathBuf) -> Result<PathBuf, String> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
+        .map_err(|e| e.to_string())?;
+    xdg_dirs.place_cache_file(name)
+        .map_err(|e| e.to_string())
+}
+
+/// Replace the file at `path` with `content` without ever leaving it half written
+///
+/// The content is written to a temporary file in the same directory, synced to disk and then
+/// moved over the original file.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let mut tmp_name = path.file_name()
+        .ok_or(format!("Invalid path '{}'", path.to_string_lossy()))?
+        .to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = File::create(&tmp_path)
+        .map_err(|e| e.to_string())?;
+    file.write_all(content)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            e.to_string()
+        })?;
+    fs::rename(&tmp_path, path)
+        .map_err(|e| e.to_string())?;
+
+    // persist the rename itself
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::absence::Absence;
+use crate::entry::{ClockinTimestamp, Entry};
+use crate::settings::NamespaceSettings;
+use super::Storage;
+
+/// Volatile storage that keeps everything in memory
+///
+/// Useful for testing and for embedding foliot without touching the file system.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: RefCell<HashMap<String, Vec<Entry>>>,
+    clockins: RefCell<HashMap<String, ClockinTimestamp>>,
+    settings: RefCell<HashMap<String, NamespaceSettings>>,
+    absences: RefCell<HashMap<String, Vec<Absence>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn namespaces(&self) -> Result<Vec<String>, String> {
+        let mut namespaces: Vec<String> = self.entries.borrow().keys()
+            .chain(self.clockins.borrow().keys())
+            .chain(self.settings.borrow().keys())
+            .chain(self.absences.borrow().keys())
+            .cloned()
+            .collect();
+        namespaces.sort();
+        namespaces.dedup();
+        Ok(namespaces)
+    }
+
+    fn entries(&self, namespace: &str) -> Result<Option<Vec<Entry>>, String> {
+        Ok(self.entries.borrow().get(namespace).cloned())
+    }
+
+    fn write_entries(&self, namespace: &str, entries: &[Entry]) -> Result<(), String> {
+        self.entries.borrow_mut().insert(namespace.to_owned(), entries.to_vec());
+        Ok(())
+    }
+
+    fn clockin(&self, namespace: &str) -> Result<Option<ClockinTimestamp>, String> {
+        Ok(self.clockins.borrow().get(namespace).cloned())
+    }
+
+    fn write_clockin(&self, namespace: &str, timestamp: &ClockinTimestamp) -> Result<(), String> {
+        self.clockins.borrow_mut().insert(namespace.to_owned(), timestamp.clone());
+        Ok(())
+    }
+
+    fn remove_clockin(&self, namespace: &str) -> Result<(), String> {
+        self.clockins.borrow_mut().remove(namespace)
+            .map(|_| ())
+            .ok_or("Path not found".to_owned())
+    }
+
+    fn settings(&self, namespace: &str) -> Result<Option<NamespaceSettings>, String> {
+        Ok(self.settings.borrow().get(namespace).cloned())
+    }
+
+    fn write_settings(&self, namespace: &str, settings: &NamespaceSettings) -> Result<(), String> {
+        self.settings.borrow_mut().insert(namespace.to_owned(), settings.clone());
+        Ok(())
+    }
+
+    fn absences(&self, namespace: &str) -> Result<Option<Vec<Absence>>, String> {
+        Ok(self.absences.borrow().get(namespace).cloned())
+    }
+
+    fn write_absences(&self, namespace: &str, absences: &[Absence]) -> Result<(), String> {
+        self.absences.borrow_mut().insert(namespace.to_owned(), absences.to_vec());
+        Ok(())
+    }
+
+    fn remove_namespace(&self, namespace: &str) -> Result<(), String> {
+        self.entries.borrow_mut().remove(namespace);
+        self.clockins.borrow_mut().remove(namespace);
+        self.settings.borrow_mut().remove(namespace);
+        self.absences.borrow_mut().remove(namespace);
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+//! Backends the [Tracker](crate::Tracker) persists its data with
+
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::absence::Absence;
+use crate::entry::{ClockinTimestamp, Entry};
+use crate::settings::NamespaceSettings;
+
+mod lock;
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod yaml;
+
+pub use lock::NamespaceLock;
+pub(crate) use lock::{cache_file, write_atomic};
+pub use memory::MemoryStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+pub use yaml::YamlStorage;
+
+pub(crate) const XDG_DIR_PREFIX: &str = "foliot";
+
+/// Name of the SQLite database in the data directory
+const SQLITE_FILE_NAME: &str = "foliot.sqlite";
+
+/// Persistent storage backends available to the command line
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StorageKind {
+    /// One YAML file per namespace (see [YamlStorage])
+    Yaml,
+
+    /// A single SQLite database (requires the `sqlite` feature)
+    Sqlite,
+}
+
+/// Persistence of entries and running clocks, split by namespace
+pub trait Storage {
+    /// Names of all namespaces that have entries, a running clock, settings or absences
+    fn namespaces(&self) -> Result<Vec<String>, String>;
+
+    /// All entries of a namespace or `None` if the namespace has no entries yet
+    fn entries(&self, namespace: &str) -> Result<Option<Vec<Entry>>, String>;
+
+    /// Replace all entries of a namespace
+    fn write_entries(&self, namespace: &str, entries: &[Entry]) -> Result<(), String>;
+
+    /// Append a single entry to a namespace
+    fn add_entry(&self, namespace: &str, entry: &Entry) -> Result<(), String> {
+        let mut entries = self.entries(namespace)?.unwrap_or_default();
+        entries.sort();
+        entries.push(entry.clone());
+        self.write_entries(namespace, &entries)
+    }
+
+    /// All entries of a namespace that overlap with `entry`
+    fn overlapping_entries(&self, namespace: &str, entry: &Entry) -> Result<Vec<Entry>, String> {
+        Ok(self.entries(namespace)?.unwrap_or_default()
+            .into_iter()
+            .filter(|e| entry.overlaps(e))
+            .collect())
+    }
+
+    /// The running clock of a namespace, if any
+    fn clockin(&self, namespace: &str) -> Result<Option<ClockinTimestamp>, String>;
+
+    /// Store the running clock of a namespace
+    fn write_clockin(&self, namespace: &str, timestamp: &ClockinTimestamp) -> Result<(), String>;
+
+    /// Remove the running clock of a namespace
+    fn remove_clockin(&self, namespace: &str) -> Result<(), String>;
+
+    /// Settings of a namespace, if any were made
+    fn settings(&self, namespace: &str) -> Result<Option<NamespaceSettings>, String>;
+
+    /// Store the settings of a namespace
+    fn write_settings(&self, namespace: &str, settings: &NamespaceSettings) -> Result<(), String>;
+
+    /// All absences of a namespace or `None` if none were recorded yet
+    fn absences(&self, namespace: &str) -> Result<Option<Vec<Absence>>, String>;
+
+    /// Replace all absences of a namespace
+    fn write_absences(&self, namespace: &str, absences: &[Absence]) -> Result<(), String>;
+
+    /// Remove all data of a namespace
+    fn remove_namespace(&self, namespace: &str) -> Result<(), String>;
+
+    /// Remove all data of this backend
+    fn destroy(self: Box<Self>) -> Result<(), String> {
+        for namespace in self.namespaces()? {
+            self.remove_namespace(&namespace)?;
+        }
+        Ok(())
+    }
+
+    /// Lock a namespace for a read-modify-write cycle
+    ///
+    /// Backends that may be accessed by multiple processes at once should override this.
+    fn lock(&self, _namespace: &str) -> Result<NamespaceLock, String> {
+        Ok(NamespaceLock::none())
+    }
+
+    /// Directory the data is stored in, if the backend is file based
+    fn data_dir(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// File the entries of a namespace are stored in, if the backend is file based
+    fn entries_file(&self, _namespace: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// File the running clock of a namespace is stored in, if the backend is file based
+    fn clockin_file(&self, _namespace: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+impl StorageKind {
+    /// Detect which backend the data in the XDG data directory is stored with
+    pub fn detect() -> Result<Self, String> {
+        let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
+            .map_err(|e| e.to_string())?;
+        if xdg_dirs.find_data_file(SQLITE_FILE_NAME).is_some() {
+            Ok(StorageKind::Sqlite)
+        } else {
+            Ok(StorageKind::Yaml)
+        }
+    }
+
+    /// Open the backend in the XDG data directory
+    pub fn open(&self) -> Result<Box<dyn Storage>, String> {
+        match self {
+            StorageKind::Yaml => Ok(Box::new(YamlStorage::new()?)),
+            #[cfg(feature = "sqlite")]
+            StorageKind::Sqlite => Ok(Box::new(SqliteStorage::new()?)),
+            #[cfg(not(feature = "sqlite"))]
+            StorageKind::Sqlite => Err("foliot was built without SQLite support (feature 'sqlite')".to_owned()),
+        }
+    }
+
+    /// Copy all data of `from` into a new backend of this kind in the XDG data directory
+    ///
+    /// Nothing is left behind if copying fails. The data is written to temporary files first and
+    /// only moved into place once complete, as the presence of an SQLite database decides which
+    /// backend is used and YAML files may already exist in the data directory.
+    pub fn create_from(&self, from: &dyn Storage) -> Result<(Box<dyn Storage>, Vec<String>), String> {
+        match self {
+            StorageKind::Yaml => {
+                let (target, namespaces) = YamlStorage::create_from(from)?;
+                Ok((Box::new(target), namespaces))
+            },
+            #[cfg(feature = "sqlite")]
+            StorageKind::Sqlite => {
+                let path = SqliteStorage::default_path()?;
+                let tmp_path = path.with_extension("sqlite.tmp");
+                if tmp_path.exists() {
+                    std::fs::remove_file(&tmp_path)
+                        .map_err(|e| e.to_string())?;
+                }
+                let staging = Box::new(SqliteStorage::open(&tmp_path)?);
+                let namespaces = match copy(from, staging.as_ref()) {
+                    Ok(namespaces) => namespaces,
+                    Err(e) => {
+                        let _ = staging.destroy();
+                        return Err(e);
+                    },
+                };
+                drop(staging);
+                std::fs::rename(&tmp_path, &path)
+                    .map_err(|e| e.to_string())?;
+                Ok((Box::new(SqliteStorage::open(&path)?), namespaces))
+            },
+            #[cfg(not(feature = "sqlite"))]
+            StorageKind::Sqlite => Err("foliot was built without SQLite support (feature 'sqlite')".to_owned()),
+        }
+    }
+}
+
+impl Display for StorageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageKind::Yaml => write!(f, "yaml"),
+            StorageKind::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+impl FromStr for StorageKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "yaml" => Ok(StorageKind::Yaml),
+            "sqlite" => Ok(StorageKind::Sqlite),
+            _ => Err(format!("unknown storage backend '{}' (expected 'yaml' or 'sqlite')", s)),
+        }
+    }
+}
+
+/// Copy all namespaces from one backend to another and return their names
+pub fn copy(from: &dyn Storage, to: &dyn Storage) -> Result<Vec<String>, String> {
+    let namespaces = from.namespaces()?;
+    for namespace in &namespaces {
+        if let Some(entries) = from.entries(namespace)? {
+            to.write_entries(namespace, &entries)?;
+        }
+        if let Some(timestamp) = from.clockin(namespace)? {
+            to.write_clockin(namespace, &timestamp)?;
+        }
+        if let Some(settings) = from.settings(namespace)? {
+            to.write_settings(namespace, &settings)?;
+        }
+        if let Some(absences) = from.absences(namespace)? {
+            to.write_absences(namespace, &absences)?;
+        }
+    }
+    Ok(namespaces)
+}
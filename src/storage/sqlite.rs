@@ -0,0 +1,278 @@
+use chrono::offset::Local;
+use chrono::DateTime;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fs;
+use std::path::*;
+
+use crate::absence::Absence;
+use crate::entry::{ClockinTimestamp, Entry};
+use crate::settings::NamespaceSettings;
+use super::{NamespaceLock, Storage, SQLITE_FILE_NAME, XDG_DIR_PREFIX};
+
+/// Storage in a single SQLite database with indexed tables
+///
+/// Entries and clocks are kept as YAML documents, next to indexed columns for their timespan.
+/// That way they stay lossless while overlaps can be found without scanning the whole history.
+pub struct SqliteStorage {
+    connection: Connection,
+    path: PathBuf,
+}
+
+impl SqliteStorage {
+    /// Open (or create) the database in the XDG data directory
+    pub fn new() -> Result<Self, String> {
+        Self::open(&Self::default_path()?)
+    }
+
+    /// Path to the database in the XDG data directory
+    pub(super) fn default_path() -> Result<PathBuf, String> {
+        let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
+            .map_err(|e| e.to_string())?;
+        xdg_dirs.place_data_file(SQLITE_FILE_NAME)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Open (or create) the database at `path`
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let connection = Connection::open(path)
+            .map_err(|e| e.to_string())?;
+        connection.execute_batch("
+            CREATE TABLE IF NOT EXISTS entries (
+                id INTEGER PRIMARY KEY,
+                namespace TEXT NOT NULL,
+                start_time INTEGER NOT NULL,
+                end_time INTEGER NOT NULL,
+                data TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS entries_start ON entries (namespace, start_time);
+            CREATE INDEX IF NOT EXISTS entries_end ON entries (namespace, end_time);
+            CREATE TABLE IF NOT EXISTS clockins (
+                namespace TEXT PRIMARY KEY,
+                data TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS settings (
+                namespace TEXT PRIMARY KEY,
+                data TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS absences (
+                namespace TEXT PRIMARY KEY,
+                data TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS namespaces (
+                namespace TEXT PRIMARY KEY
+            );
+        ").map_err(|e| e.to_string())?;
+        Ok(SqliteStorage { connection, path: path.to_owned() })
+    }
+
+    /// Insert an entry without any further checks
+    fn insert_entry(connection: &Connection, namespace: &str, entry: &Entry) -> Result<(), String> {
+        let data = serde_yaml::to_string(entry)
+            .map_err(|e| e.to_string())?;
+        connection.execute(
+            "INSERT INTO entries (namespace, start_time, end_time, data) VALUES (?1, ?2, ?3, ?4)",
+            params![namespace, timestamp(&entry.start_time), timestamp(&entry.end_time), data],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Remember that a namespace has entries, even if all of them are removed
+    fn register_namespace(connection: &Connection, namespace: &str) -> Result<(), String> {
+        connection.execute("INSERT OR IGNORE INTO namespaces (namespace) VALUES (?1)", params![namespace])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Query entries and deserialize them
+    fn query_entries(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Entry>, String> {
+        let mut statement = self.connection.prepare(sql)
+            .map_err(|e| e.to_string())?;
+        let rows = statement.query_map(params, |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        rows.map(|data| {
+            let data = data.map_err(|e| e.to_string())?;
+            serde_yaml::from_str(&data).map_err(|e| e.to_string())
+        }).collect()
+    }
+}
+
+/// Indexable representation of a point in time
+fn timestamp(time: &DateTime<Local>) -> i64 {
+    time.timestamp()
+}
+
+impl Storage for SqliteStorage {
+    fn namespaces(&self) -> Result<Vec<String>, String> {
+        let mut statement = self.connection.prepare(
+            "SELECT namespace FROM entries UNION SELECT namespace FROM clockins UNION SELECT namespace FROM settings \
+             UNION SELECT namespace FROM absences UNION SELECT namespace FROM namespaces ORDER BY namespace"
+        ).map_err(|e| e.to_string())?;
+        let rows = statement.query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.map(|r| r.map_err(|e| e.to_string())).collect()
+    }
+
+    fn entries(&self, namespace: &str) -> Result<Option<Vec<Entry>>, String> {
+        let entries = self.query_entries(
+            "SELECT data FROM entries WHERE namespace = ?1 ORDER BY start_time, end_time",
+            params![namespace],
+        )?;
+        if !entries.is_empty() {
+            return Ok(Some(entries));
+        }
+        let registered = self.connection.query_row(
+            "SELECT EXISTS (SELECT 1 FROM namespaces WHERE namespace = ?1)",
+            params![namespace],
+            |row| row.get::<_, bool>(0),
+        ).map_err(|e| e.to_string())?;
+        Ok(if registered { Some(entries) } else { None })
+    }
+
+    fn write_entries(&self, namespace: &str, entries: &[Entry]) -> Result<(), String> {
+        let transaction = self.connection.unchecked_transaction()
+            .map_err(|e| e.to_string())?;
+        transaction.execute("DELETE FROM entries WHERE namespace = ?1", params![namespace])
+            .map_err(|e| e.to_string())?;
+        Self::register_namespace(&transaction, namespace)?;
+        for entry in entries {
+            Self::insert_entry(&transaction, namespace, entry)?;
+        }
+        transaction.commit()
+            .map_err(|e| e.to_string())
+    }
+
+    fn add_entry(&self, namespace: &str, entry: &Entry) -> Result<(), String> {
+        Self::register_namespace(&self.connection, namespace)?;
+        Self::insert_entry(&self.connection, namespace, entry)
+    }
+
+    fn overlapping_entries(&self, namespace: &str, entry: &Entry) -> Result<Vec<Entry>, String> {
+        let candidates = self.query_entries(
+            "SELECT data FROM entries WHERE namespace = ?1 AND start_time <= ?3 AND end_time >= ?2",
+            params![namespace, timestamp(&entry.start_time), timestamp(&entry.end_time)],
+        )?;
+        Ok(candidates.into_iter().filter(|e| entry.overlaps(e)).collect())
+    }
+
+    fn clockin(&self, namespace: &str) -> Result<Option<ClockinTimestamp>, String> {
+        let data: Option<String> = self.connection.query_row(
+            "SELECT data FROM clockins WHERE namespace = ?1",
+            params![namespace],
+            |row| row.get(0),
+        ).optional().map_err(|e| e.to_string())?;
+        data.map(|d| serde_yaml::from_str(&d).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    fn write_clockin(&self, namespace: &str, timestamp: &ClockinTimestamp) -> Result<(), String> {
+        let data = serde_yaml::to_string(timestamp)
+            .map_err(|e| e.to_string())?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO clockins (namespace, data) VALUES (?1, ?2)",
+            params![namespace, data],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn remove_clockin(&self, namespace: &str) -> Result<(), String> {
+        let removed = self.connection.execute("DELETE FROM clockins WHERE namespace = ?1", params![namespace])
+            .map_err(|e| e.to_string())?;
+        if removed == 0 {
+            return Err("Path not found".to_owned());
+        }
+        Ok(())
+    }
+
+    fn settings(&self, namespace: &str) -> Result<Option<NamespaceSettings>, String> {
+        let data: Option<String> = self.connection.query_row(
+            "SELECT data FROM settings WHERE namespace = ?1",
+            params![namespace],
+            |row| row.get(0),
+        ).optional().map_err(|e| e.to_string())?;
+        data.map(|d| serde_yaml::from_str(&d).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    fn write_settings(&self, namespace: &str, settings: &NamespaceSettings) -> Result<(), String> {
+        let data = serde_yaml::to_string(settings)
+            .map_err(|e| e.to_string())?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO settings (namespace, data) VALUES (?1, ?2)",
+            params![namespace, data],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn absences(&self, namespace: &str) -> Result<Option<Vec<Absence>>, String> {
+        let data: Option<String> = self.connection.query_row(
+            "SELECT data FROM absences WHERE namespace = ?1",
+            params![namespace],
+            |row| row.get(0),
+        ).optional().map_err(|e| e.to_string())?;
+        data.map(|d| serde_yaml::from_str(&d).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    fn write_absences(&self, namespace: &str, absences: &[Absence]) -> Result<(), String> {
+        let data = serde_yaml::to_string(absences)
+            .map_err(|e| e.to_string())?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO absences (namespace, data) VALUES (?1, ?2)",
+            params![namespace, data],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn remove_namespace(&self, namespace: &str) -> Result<(), String> {
+        for table in ["entries", "clockins", "settings", "absences", "namespaces"] {
+            self.connection.execute(&format!("DELETE FROM {} WHERE namespace = ?1", table), params![namespace])
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn destroy(self: Box<Self>) -> Result<(), String> {
+        let path = self.path.clone();
+        self.connection.close()
+            .map_err(|(_, e)| e.to_string())?;
+        fs::remove_file(path)
+            .map_err(|e| e.to_string())
+    }
+
+    fn lock(&self, namespace: &str) -> Result<NamespaceLock, String> {
+        NamespaceLock::acquire(namespace)
+    }
+
+    fn data_dir(&self) -> Option<PathBuf> {
+        self.path.parent().map(|p| p.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn open(name: &str) -> SqliteStorage {
+        let path = std::env::temp_dir().join(format!("foliot-test-{}-{}.sqlite", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        SqliteStorage::open(&path).unwrap()
+    }
+
+    #[test]
+    fn namespaces_without_entries_are_kept() {
+        let storage = Box::new(open("empty"));
+        let start = Local.with_ymd_and_hms(2024, 1, 15, 9, 0, 0).unwrap();
+        let entry = Entry::create(start, start + chrono::Duration::hours(1), None, Vec::new());
+        storage.add_entry("work", &entry).unwrap();
+        storage.write_entries("work", &[]).unwrap();
+
+        assert_eq!(storage.namespaces().unwrap(), vec!["work".to_owned()]);
+        assert_eq!(storage.entries("work").unwrap(), Some(Vec::new()));
+        assert_eq!(storage.entries("other").unwrap(), None);
+
+        storage.remove_namespace("work").unwrap();
+        assert!(storage.namespaces().unwrap().is_empty());
+        storage.destroy().unwrap();
+    }
+}
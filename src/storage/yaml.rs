@@ -0,0 +1,216 @@
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::path::*;
+
+use crate::absence::Absence;
+use crate::entry::{ClockinTimestamp, Entry};
+use crate::settings::NamespaceSettings;
+use super::{lock, NamespaceLock, Storage, XDG_DIR_PREFIX};
+
+/// Suffixes of the files that belong to a namespace besides its entries
+const FILE_SUFFIXES: [&str; 3] = ["-clockin", "-settings", "-absences"];
+
+/// Subdirectory of the data directory that migrated files are written to before moving them into place
+const STAGING_DIR: &str = "migration.tmp";
+
+/// Default storage with one human-editable YAML file per namespace in the XDG data directory
+///
+/// Entries are stored in `<namespace>.yaml`, the running clock in `<namespace>-clockin.yaml`,
+/// the settings in `<namespace>-settings.yaml` and absences in `<namespace>-absences.yaml`.
+pub struct YamlStorage {
+    xdg_dirs: xdg::BaseDirectories,
+}
+
+impl YamlStorage {
+    pub fn new() -> Result<Self, String> {
+        let xdg_dirs = xdg::BaseDirectories::with_prefix(XDG_DIR_PREFIX)
+            .map_err(|e| e.to_string())?;
+        Ok(YamlStorage { xdg_dirs })
+    }
+
+    /// Copy all data of `from` into the data directory and return the names of the copied namespaces
+    ///
+    /// The files are written to a staging directory and only moved into place once all are complete.
+    /// If moving them fails, the files it created are removed again while files that existed before stay.
+    pub(super) fn create_from(from: &dyn Storage) -> Result<(Self, Vec<String>), String> {
+        let target = YamlStorage::new()?;
+        let staging = YamlStorage {
+            xdg_dirs: xdg::BaseDirectories::with_profile(XDG_DIR_PREFIX, STAGING_DIR)
+                .map_err(|e| e.to_string())?,
+        };
+        let staging_dir = staging.xdg_dirs.get_data_home();
+        if staging_dir.exists() {
+            fs::remove_dir_all(&staging_dir)
+                .map_err(|e| e.to_string())?;
+        }
+
+        let result = super::copy(from, &staging)
+            .and_then(|namespaces| target.move_files_from(&staging_dir).map(|_| namespaces));
+        let _ = fs::remove_dir_all(&staging_dir);
+        result.map(|namespaces| (target, namespaces))
+    }
+
+    /// Move all files in `dir` into the data directory, removing the ones it created if one fails
+    fn move_files_from(&self, dir: &Path) -> Result<(), String> {
+        if !dir.exists() {
+            return Ok(());
+        }
+
+        let mut created = Vec::new();
+        for file in fs::read_dir(dir).map_err(|e| e.to_string())? {
+            let moved = file.map_err(|e| e.to_string()).and_then(|file| {
+                let path = self.xdg_dirs.place_data_file(file.file_name())
+                    .map_err(|e| e.to_string())?;
+                let existed = path.exists();
+                fs::rename(file.path(), &path)
+                    .map_err(|e| e.to_string())?;
+                Ok((path, existed))
+            });
+            match moved {
+                Ok((path, false)) => created.push(path),
+                Ok(_) => (),
+                Err(e) => {
+                    for path in created {
+                        let _ = fs::remove_file(path);
+                    }
+                    return Err(e);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Relative path to the file that entries are collected in
+    fn entries_path(namespace: &str) -> PathBuf {
+        PathBuf::from(namespace)
+            .with_extension("yaml")
+    }
+
+    /// Relative path to the file that contains the last clockin timestamp
+    fn clockin_path(namespace: &str) -> PathBuf {
+        PathBuf::from(format!("{}-clockin", namespace))
+            .with_extension("yaml")
+    }
+
+    /// Relative path to the file that contains the settings of a namespace
+    fn settings_path(namespace: &str) -> PathBuf {
+        PathBuf::from(format!("{}-settings", namespace))
+            .with_extension("yaml")
+    }
+
+    /// Relative path to the file that contains the absences of a namespace
+    fn absences_path(namespace: &str) -> PathBuf {
+        PathBuf::from(format!("{}-absences", namespace))
+            .with_extension("yaml")
+    }
+
+    /// Deserialize a file with the relative path `path` in the data directory
+    fn read_data_file<T: for<'a> Deserialize<'a>>(&self, path: &impl AsRef<Path>) -> Result<Option<T>, String> {
+        let abs_path = match self.xdg_dirs.find_data_file(path) {
+            Some(abs_path) => abs_path,
+            None => return Ok(None),
+        };
+        let content = fs::read(abs_path)
+            .map_err(|e| e.to_string())?;
+        serde_yaml::from_slice(&content)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Delete a file with the relative path `path` in the data directory
+    fn remove_data_file(&self, path: &impl AsRef<Path>) -> Result<(), String> {
+        let abs_path = self.xdg_dirs.find_data_file(path)
+            .ok_or("Path not found".to_owned())?;
+        fs::remove_file(abs_path)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Serialize a file with the relative path `path` in the data directory
+    fn write_data_file(&self, path: &impl AsRef<Path>, data: impl Serialize) -> Result<(), String> {
+        let abs_path = self.xdg_dirs.place_data_file(path)
+            .map_err(|e| e.to_string())?;
+        let content = serde_yaml::to_string(&data)
+            .map_err(|e| e.to_string())?;
+        lock::write_atomic(&abs_path, content.as_bytes())
+    }
+}
+
+impl Storage for YamlStorage {
+    fn namespaces(&self) -> Result<Vec<String>, String> {
+        let mut namespaces: Vec<String> = self.xdg_dirs.list_data_files("")
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "yaml"))
+            .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .map(|s| FILE_SUFFIXES.iter().find_map(|suffix| s.strip_suffix(suffix)).map(|s| s.to_owned()).unwrap_or(s))
+            .collect();
+        namespaces.sort();
+        namespaces.dedup();
+        Ok(namespaces)
+    }
+
+    fn entries(&self, namespace: &str) -> Result<Option<Vec<Entry>>, String> {
+        self.read_data_file(&Self::entries_path(namespace))
+    }
+
+    fn write_entries(&self, namespace: &str, entries: &[Entry]) -> Result<(), String> {
+        self.write_data_file(&Self::entries_path(namespace), entries)
+    }
+
+    fn clockin(&self, namespace: &str) -> Result<Option<ClockinTimestamp>, String> {
+        self.read_data_file(&Self::clockin_path(namespace))
+    }
+
+    fn write_clockin(&self, namespace: &str, timestamp: &ClockinTimestamp) -> Result<(), String> {
+        self.write_data_file(&Self::clockin_path(namespace), timestamp)
+    }
+
+    fn remove_clockin(&self, namespace: &str) -> Result<(), String> {
+        self.remove_data_file(&Self::clockin_path(namespace))
+    }
+
+    fn settings(&self, namespace: &str) -> Result<Option<NamespaceSettings>, String> {
+        self.read_data_file(&Self::settings_path(namespace))
+    }
+
+    fn write_settings(&self, namespace: &str, settings: &NamespaceSettings) -> Result<(), String> {
+        self.write_data_file(&Self::settings_path(namespace), settings)
+    }
+
+    fn absences(&self, namespace: &str) -> Result<Option<Vec<Absence>>, String> {
+        self.read_data_file(&Self::absences_path(namespace))
+    }
+
+    fn write_absences(&self, namespace: &str, absences: &[Absence]) -> Result<(), String> {
+        self.write_data_file(&Self::absences_path(namespace), absences)
+    }
+
+    fn remove_namespace(&self, namespace: &str) -> Result<(), String> {
+        let paths = [
+            Self::entries_path(namespace),
+            Self::clockin_path(namespace),
+            Self::settings_path(namespace),
+            Self::absences_path(namespace),
+        ];
+        for path in paths {
+            if self.xdg_dirs.find_data_file(&path).is_some() {
+                self.remove_data_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn lock(&self, namespace: &str) -> Result<NamespaceLock, String> {
+        NamespaceLock::acquire(namespace)
+    }
+
+    fn data_dir(&self) -> Option<PathBuf> {
+        self.xdg_dirs.find_data_file("")
+    }
+
+    fn entries_file(&self, namespace: &str) -> Option<PathBuf> {
+        self.xdg_dirs.find_data_file(Self::entries_path(namespace))
+    }
+
+    fn clockin_file(&self, namespace: &str) -> Option<PathBuf> {
+        self.xdg_dirs.find_data_file(Self::clockin_path(namespace))
+    }
+}
@@ -0,0 +1,446 @@
+use chrono::{Datelike, Duration, NaiveDate, Months};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use tabled::Tabled;
+
+use crate::duration::HumanDuration;
+use crate::entry::Entry;
+use crate::schedule::Schedule;
+use crate::time::now;
+
+/// Entry formatted for displaying a summary for a group of entries (e.g. a month)
+#[derive(Clone, Debug, PartialEq, PartialOrd, Tabled, Serialize)]
+pub struct SummaryTableItem {
+    pub group: String,
+
+    #[tabled(rename = "total hours")]
+    #[serde(rename = "total_minutes", serialize_with = "HumanDuration::serialize_minutes")]
+    pub total_hours: HumanDuration,
+
+    #[tabled(rename = "decimal", display_with = "display_decimal")]
+    #[serde(rename = "total_hours")]
+    pub decimal_hours: f64,
+
+    #[tabled(rename = "hours / week", display_with = "display_hours")]
+    pub hours_per_week: Option<f64>,
+
+    #[tabled(rename = "hours / day", display_with = "display_hours")]
+    pub hours_per_day: Option<f64>,
+
+    pub days: usize,
+
+    /// Days work is expected on in the period, without public holidays
+    #[tabled(rename = "working days", display_with = "display_count")]
+    pub working_days: Option<usize>,
+
+    #[tabled(rename = "entries")]
+    #[serde(rename = "entries")]
+    pub nitems: usize,
+
+    /// Hours expected to be worked in the period (up to today)
+    #[tabled(rename = "expected", display_with = "display_hours")]
+    pub expected_hours: Option<f64>,
+
+    /// Balance of worked and expected hours at the end of the period (see [Balance])
+    #[tabled(display_with = "display_balance")]
+    pub balance: Option<f64>,
+
+    /// Public holidays in the period (only for days and weeks)
+    #[tabled(display_with = "display_holidays")]
+    pub holidays: Vec<String>,
+
+    /// Dates with entries, so totals count days with entries in several groups only once
+    #[tabled(skip)]
+    #[serde(skip)]
+    pub dates: Vec<NaiveDate>,
+}
+
+/// Worked and expected hours since the target of a [Schedule] applies
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Balance {
+    pub since: NaiveDate,
+    pub until: NaiveDate,
+    pub carry_over: f64,
+    pub worked_hours: f64,
+    pub expected_hours: f64,
+
+    /// Days with an absence, which do not count towards the expected hours
+    pub absent_days: usize,
+
+    /// Public holidays, which do not count towards the expected hours either
+    pub holidays: usize,
+
+    /// Carry-over plus worked minus expected hours
+    pub balance: f64,
+}
+
+/// Criterion to group entries by for a summary
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Grouping {
+    /// Day of the start of the entry
+    Day,
+
+    /// ISO week of the start of the entry
+    Week,
+
+    /// Calendar month of the start of the entry
+    Month,
+
+    /// Quarter of the year of the start of the entry
+    Quarter,
+
+    /// Year of the start of the entry
+    Year,
+
+    /// Project of the entry
+    Project,
+}
+
+/// Label for entries without a project in per-project summaries
+const NO_PROJECT: &str = "(no project)";
+
+impl SummaryTableItem {
+    /// Create a summary row for a group of entries spanning `weeks` weeks
+    ///
+    /// The average hours per week are omitted if `weeks` is `None`.
+    fn create(group: String, entries: Vec<Entry>, weeks: Option<f64>) -> Self {
+        let mut dates: Vec<NaiveDate> = entries.iter().map(|e| e.start_time.date_naive()).collect();
+        dates.sort();
+        dates.dedup();
+
+        let total_hours: HumanDuration = entries.iter().map(|e| e.duration()).sum();
+        SummaryTableItem::with_totals(group, total_hours, weeks, dates, entries.len())
+    }
+
+    /// Accumulate multiple summary items into a single "Total" row
+    ///
+    /// The balance is the one of the last item, as it already includes the ones before.
+    pub fn total(items: &[SummaryTableItem]) -> Self {
+        let expected: Vec<f64> = items.iter().filter_map(|i| i.expected_hours).collect();
+        let working_days: Vec<usize> = items.iter().filter_map(|i| i.working_days).collect();
+        let mut dates: Vec<NaiveDate> = items.iter().flat_map(|i| i.dates.iter().copied()).collect();
+        dates.sort();
+        dates.dedup();
+        let mut total = SummaryTableItem::with_totals(
+            "Total".to_owned(),
+            items.iter().map(|i| i.total_hours).sum(),
+            None,
+            dates,
+            items.iter().map(|i| i.nitems).sum(),
+        );
+        total.working_days = (!working_days.is_empty()).then(|| working_days.iter().sum());
+        total.expected_hours = (!expected.is_empty()).then(|| expected.iter().sum());
+        total.balance = items.last().and_then(|i| i.balance);
+        total
+    }
+
+    /// Derive the averages from the totals of a group
+    fn with_totals(group: String, total_hours: HumanDuration, weeks: Option<f64>, dates: Vec<NaiveDate>, nitems: usize) -> Self {
+        let decimal_hours = total_hours.decimal_hours();
+        let days = dates.len();
+        SummaryTableItem {
+            group,
+            total_hours, decimal_hours,
+            hours_per_week: weeks.filter(|w| *w > 0.0).map(|w| decimal_hours / w),
+            hours_per_day: Some(days).filter(|d| *d > 0).map(|d| decimal_hours / d as f64),
+            days, nitems,
+            working_days: None,
+            expected_hours: None,
+            balance: None,
+            holidays: Vec::new(),
+            dates,
+        }
+    }
+}
+
+impl Balance {
+    /// Balance of `entries` from the start of the target up to and including `until`
+    ///
+    /// Returns `None` if the schedule has no target.
+    pub fn compute(schedule: &Schedule, entries: &[Entry], until: NaiveDate) -> Option<Self> {
+        let target = schedule.target.as_ref()?;
+        let worked: HumanDuration = entries.iter()
+            .filter(|e| (target.since..=until).contains(&e.start_time.date_naive()))
+            .map(|e| e.duration())
+            .sum();
+        let expected_hours = schedule.expected_hours_between(target.since, until);
+        let absent_days = target.since.iter_days()
+            .take_while(|d| *d <= until)
+            .filter(|d| schedule.is_absent(*d))
+            .count();
+
+        let holidays = schedule.holidays_between(target.since, until).len();
+
+        let worked_hours = worked.decimal_hours();
+        Some(Balance {
+            since: target.since,
+            until,
+            carry_over: target.carry_over,
+            worked_hours, expected_hours, absent_days, holidays,
+            balance: target.carry_over + worked_hours - expected_hours,
+        })
+    }
+}
+
+impl Grouping {
+    /// Name of the group an entry belongs to
+    fn key(&self, entry: &Entry) -> String {
+        let start = entry.start_time;
+        // all keys of periods sort chronologically
+        match self {
+            Grouping::Day => start.format("%Y-%m-%d %a").to_string(),
+            Grouping::Week => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            },
+            // for now adding the month number insures correct sorting
+            Grouping::Month => start.format("%Y/%m %B").to_string(),
+            Grouping::Quarter => format!("{} Q{}", start.year(), start.month0() / 3 + 1),
+            Grouping::Year => start.year().to_string(),
+            Grouping::Project => entry.project.clone().unwrap_or(NO_PROJECT.to_owned()),
+        }
+    }
+
+    /// First day and number of days of the period `date` belongs to (`None` for projects)
+    fn period(&self, date: NaiveDate) -> Option<(NaiveDate, i64)> {
+        let period = match self {
+            Grouping::Day => (date, 1),
+            Grouping::Week => {
+                let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                (start, 7)
+            },
+            Grouping::Month => {
+                let start = NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap();
+                (start, days_until(start, Months::new(1)))
+            },
+            Grouping::Quarter => {
+                let start = NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1).unwrap();
+                (start, days_until(start, Months::new(3)))
+            },
+            Grouping::Year => {
+                let start = NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap();
+                (start, days_until(start, Months::new(12)))
+            },
+            Grouping::Project => return None,
+        };
+        Some(period)
+    }
+
+    /// Number of weeks the group of `entries` spans (`None` for groups shorter than a week)
+    ///
+    /// Projects span all days of the summary from the first to the last one in `span`.
+    /// If `today` is given, days of the period after it are not counted.
+    fn weeks(&self, entries: &[Entry], span: (NaiveDate, NaiveDate), today: Option<NaiveDate>) -> Option<f64> {
+        let (start, mut days) = match (self, self.period(first_day(entries))) {
+            (Grouping::Day, _) => return None,
+            (_, Some(period)) => period,
+            (_, None) => match (span.1 - span.0).num_days() + 1 {
+                days if days < 7 => return None,
+                days => (span.0, days),
+            },
+        };
+
+        if let Some(today) = today.filter(|t| *t >= start) {
+            days = days.min((today - start).num_days() + 1);
+        }
+        Some(days as f64 / 7.0)
+    }
+}
+
+impl Display for Grouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Grouping::Day => write!(f, "day"),
+            Grouping::Week => write!(f, "week"),
+            Grouping::Month => write!(f, "month"),
+            Grouping::Quarter => write!(f, "quarter"),
+            Grouping::Year => write!(f, "year"),
+            Grouping::Project => write!(f, "project"),
+        }
+    }
+}
+
+impl FromStr for Grouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(Grouping::Day),
+            "week" => Ok(Grouping::Week),
+            "month" => Ok(Grouping::Month),
+            "quarter" => Ok(Grouping::Quarter),
+            "year" => Ok(Grouping::Year),
+            "project" => Ok(Grouping::Project),
+            _ => Err(format!("unknown grouping '{}' (expected day, week, month, quarter, year or project)", s)),
+        }
+    }
+}
+
+fn display_hours(hours: &Option<f64>) -> String {
+    match hours {
+        Some(hours) => display_decimal(hours),
+        None => "-".to_owned(),
+    }
+}
+
+fn display_decimal(hours: &f64) -> String {
+    format!("{:.2}", hours)
+}
+
+fn display_count(count: &Option<usize>) -> String {
+    match count {
+        Some(count) => count.to_string(),
+        None => "-".to_owned(),
+    }
+}
+
+fn display_holidays(holidays: &[String]) -> String {
+    holidays.join(", ")
+}
+
+fn display_balance(balance: &Option<f64>) -> String {
+    match balance {
+        Some(balance) => format!("{:+.2}", balance),
+        None => "-".to_owned(),
+    }
+}
+
+/// Day of the earliest of `entries`
+fn first_day(entries: &[Entry]) -> NaiveDate {
+    entries.iter().map(|e| e.start_time.date_naive()).min().unwrap()
+}
+
+/// Number of days from `date` to the same day `months` later
+fn days_until(date: NaiveDate, months: Months) -> i64 {
+    let later = date.checked_add_months(months).unwrap();
+    later.signed_duration_since(date).num_days()
+}
+
+/// Create a summary of the given entries, grouped by `grouping` and sorted by group
+///
+/// With `partial` the averages of the current period only take its elapsed weeks into account.
+/// Periods get their working days and holidays from `schedule` and, if it has a target, their
+/// expected hours and the [Balance] at their end (or today for the current one).
+pub fn summarize(entries: Vec<Entry>, grouping: Grouping, partial: bool, schedule: &Schedule) -> Vec<SummaryTableItem> {
+    let mut groups: HashMap<String, Vec<Entry>> = HashMap::new();
+    let today = now().date_naive();
+    let all_entries = if schedule.target.is_some() { entries.clone() } else { Vec::new() };
+    let span = (
+        entries.iter().map(|e| e.start_time.date_naive()).min().unwrap_or(today),
+        entries.iter().map(|e| e.end_time.date_naive()).max().unwrap_or(today),
+    );
+
+    for entry in entries {
+        groups.entry(grouping.key(&entry)).or_default().push(entry);
+    }
+
+    let mut table_items: Vec<SummaryTableItem> = groups.drain()
+        .map(|(group, entries)| {
+            let weeks = grouping.weeks(&entries, span, partial.then_some(today));
+            let period = grouping.period(first_day(&entries));
+            let mut item = SummaryTableItem::create(group, entries, weeks);
+            let Some((start, days)) = period else {
+                return item;
+            };
+
+            let end = start + Duration::days(days - 1);
+            item.working_days = Some(schedule.working_days_between(start, if partial { end.min(today) } else { end }));
+            if matches!(grouping, Grouping::Day | Grouping::Week) {
+                item.holidays = schedule.holidays_between(start, end).into_iter().map(|h| h.name).collect();
+            }
+            if let Some(target) = schedule.target.as_ref().filter(|t| end.min(today) >= t.since) {
+                let end = end.min(today);
+                item.expected_hours = Some(schedule.expected_hours_between(start.max(target.since), end));
+                item.balance = Balance::compute(schedule, &all_entries, end).map(|b| b.balance);
+            }
+            item
+        })
+        .collect();
+    table_items.sort_by(|a, b| a.group.cmp(&b.group));
+    table_items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Target;
+    use crate::time::at;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn entry(from: &str, to: &str) -> Entry {
+        Entry::create(at(from), at(to), None, Vec::new())
+    }
+
+    fn entries() -> Vec<Entry> {
+        vec![
+            entry("2024-01-12 09:00", "2024-01-12 14:00"),
+            entry("2024-01-15 09:00", "2024-01-15 17:00"),
+            entry("2024-01-16 09:00", "2024-01-16 15:00"),
+        ]
+    }
+
+    #[test]
+    fn balance_counts_the_hours_since_the_target_applies() {
+        let schedule = Schedule::new(Some(Target::weekly(40.0, date("2024-01-15")).with_carry_over(2.0)));
+
+        let balance = Balance::compute(&schedule, &entries(), date("2024-01-16")).unwrap();
+        assert_eq!(balance.worked_hours, 14.0);
+        assert_eq!(balance.expected_hours, 16.0);
+        assert_eq!(balance.balance, 0.0);
+
+        let balance = Balance::compute(&schedule, &entries(), date("2024-01-17")).unwrap();
+        assert_eq!(balance.expected_hours, 24.0);
+        assert_eq!(balance.balance, -8.0);
+    }
+
+    #[test]
+    fn balance_needs_a_target() {
+        assert_eq!(Balance::compute(&Schedule::new(None), &entries(), date("2024-01-16")), None);
+    }
+
+    #[test]
+    fn summary_shows_expected_hours_and_balance_per_period() {
+        let schedule = Schedule::new(Some(Target::weekly(40.0, date("2024-01-15"))));
+        let items = summarize(entries(), Grouping::Week, false, &schedule);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].group, "2024-W02");
+        assert_eq!(items[0].expected_hours, None);
+        assert_eq!(items[0].balance, None);
+        assert_eq!(items[1].group, "2024-W03");
+        assert_eq!(items[1].expected_hours, Some(40.0));
+        assert_eq!(items[1].balance, Some(-26.0));
+    }
+
+    #[test]
+    fn projects_average_over_all_weeks_of_the_summary() {
+        let mut entries = entries();
+        entries[1].project = Some("foliot".to_owned());
+        entries.push(entry("2024-01-25 09:00", "2024-01-25 13:00"));
+        let items = summarize(entries.clone(), Grouping::Project, false, &Schedule::new(None));
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].group, "foliot");
+        assert_eq!(items[1].hours_per_week, Some(8.0 / 2.0));
+
+        entries.truncate(2);
+        let items = summarize(entries, Grouping::Project, false, &Schedule::new(None));
+        assert_eq!(items[1].hours_per_week, None);
+    }
+
+    #[test]
+    fn totals_count_days_with_entries_in_several_groups_once() {
+        let mut entries = entries();
+        entries.push(entry("2024-01-16 16:00", "2024-01-16 17:00"));
+        entries[3].project = Some("foliot".to_owned());
+        let items = summarize(entries, Grouping::Project, false, &Schedule::new(None));
+
+        assert_eq!(items.iter().map(|i| i.days).sum::<usize>(), 4);
+        assert_eq!(SummaryTableItem::total(&items).days, 3);
+    }
+}
@@ -0,0 +1,341 @@
+use chrono::offset::Local;
+use chrono::{DateTime, Datelike, Days, DurationRound, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use regex::Regex;
+
+/// Examples of the forms accepted by [parse_datetime], listed when a time can not be parsed
+const ACCEPTED_FORMS: &str = "now, 14:30, yesterday 9:30, monday 14:00, last friday, -15m, 2h ago, 1h30m ago, \
+                              2015-09-18, 2015-09-18 14:30, 2015-09-18T14:30:00 or 18.09.2015 14:30";
+
+/// Return current time in the current timezone
+pub fn now() -> DateTime<Local> {
+    Local::now().duration_round(chrono::Duration::minutes(1)).unwrap()
+}
+
+/// Convert a date and time without timezone to local time
+///
+/// Fails for times that are skipped or occur twice when the clocks change for daylight saving time.
+pub fn local_datetime(time: &NaiveDateTime) -> Result<DateTime<Local>, String> {
+    Local.from_local_datetime(time).single()
+        .ok_or(format!("Time '{}' is ambiguous or does not exist in the local timezone", time))
+}
+
+/// Convert a number of hours, like the `2.5` of `foliot clock 2.5`, to a duration
+///
+/// Fails unless the hours are positive and short enough to represent.
+pub fn duration_from_hours(hours: f64) -> Result<chrono::Duration, String> {
+    let minutes = (hours * 60.0).round();
+    if !minutes.is_finite() || minutes <= 0.0 || minutes > i32::MAX as f64 {
+        return Err(format!("Invalid number of hours '{}' (expected a positive number, e.g. 2.5)", hours));
+    }
+    Ok(chrono::Duration::minutes(minutes as i64))
+}
+
+/// Parse a duration given either as `HH:MM` or as decimal hours, like in a timesheet
+pub(crate) fn parse_hours(s: &str) -> Result<chrono::Duration, String> {
+    let hours = match s.split_once(':') {
+        Some((hours, minutes)) => match (hours.parse::<u64>(), minutes.parse::<u64>()) {
+            (Ok(hours), Ok(minutes)) if minutes < 60 => Some(hours as f64 + minutes as f64 / 60.0),
+            _ => None,
+        },
+        None => s.parse::<f64>().ok(),
+    };
+    let hours = hours.ok_or(format!("unable to parse duration '{}'", s))?;
+    duration_from_hours(hours)
+        .map_err(|_| format!("invalid duration '{}' (expected a positive number of hours, e.g. 1.5 or 1:30)", s))
+}
+
+/// Parse a datetime as given on the command line
+///
+/// Besides absolute dates and times this accepts times relative to now (`-15m`, `2h ago`) and
+/// days relative to today (`yesterday 9:30`, `monday 14:00`, `last friday`).
+/// Days without a time refer to their start, times without a day to their last occurrence.
+pub fn parse_datetime(s: &str) -> Result<NaiveDateTime, String> {
+    parse_datetime_at(s, now())
+        .ok_or(format!("unable to parse time '{}' (accepted forms: {})", s, ACCEPTED_FORMS))
+}
+
+/// Parse a datetime based on the time alone (either today or yesterday)
+pub fn parse_time(s: &str) -> Result<NaiveDateTime, String> {
+    let time = parse_clock_time(s)
+        .ok_or(format!("unable to parse time '{}'", s))?;
+    Ok(last_occurrence(time, now().naive_local()))
+}
+
+/// Parse the end of a timespan starting at `start`
+///
+/// A time without a day refers to the day of `start`, or the next one if it is earlier than `start`.
+pub fn parse_end_time(s: &str, start: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    match parse_clock_time(s.trim()) {
+        Some(time) if time >= start.time() => Ok(start.date().and_time(time)),
+        Some(time) => Ok(start.date().succ_opt().ok_or("date out of range")?.and_time(time)),
+        None => parse_datetime(s),
+    }
+}
+
+/// Parse a timespan like `09:00-12:30` or `yesterday 22:00-01:00`
+pub fn parse_time_range(s: &str) -> Result<(NaiveDateTime, NaiveDateTime), String> {
+    // times may contain dashes as well, so try every one as separator
+    s.match_indices('-')
+        .find_map(|(i, _)| {
+            let start = parse_datetime(&s[..i]).ok()?;
+            let end = parse_end_time(&s[i + 1..], start).ok()?;
+            Some((start, end))
+        })
+        .ok_or(format!("unable to parse time range '{}' (expected two times separated by '-', e.g. 09:00-12:30)", s))
+}
+
+/// Parse a duration like `15m`, `2h`, `1h30m` or `3 days`
+pub fn parse_duration(s: &str) -> Result<chrono::Duration, String> {
+    parse_lowercase_duration(&s.to_lowercase())
+        .ok_or(format!("unable to parse duration '{}' (accepted forms: 15m, 2h, 1h30m or 3 days)", s))
+}
+
+/// Parse a datetime with relative expressions referring to `now`
+///
+/// Durations are subtracted from `now` in real time, so they stay exact across daylight saving changes.
+fn parse_datetime_at(s: &str, now: DateTime<Local>) -> Option<NaiveDateTime> {
+    let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some(datetime) = parse_absolute(&s) {
+        return Some(datetime);
+    }
+
+    let s = s.to_lowercase();
+    if s == "now" {
+        return Some(now.naive_local());
+    }
+    if let Some(duration) = s.strip_prefix('-').or(s.strip_suffix(" ago")).and_then(parse_lowercase_duration) {
+        return now.checked_sub_signed(duration).map(|t| t.naive_local());
+    }
+    if let Some(time) = parse_clock_time(&s) {
+        return Some(last_occurrence(time, now.naive_local()));
+    }
+
+    let (day, time) = match s.rsplit_once(' ').and_then(|(day, time)| Some((day, parse_clock_time(time)?))) {
+        Some((day, time)) => (day, time),
+        None => (s.as_str(), NaiveTime::MIN),
+    };
+    parse_day(day, now.date_naive()).map(|d| d.and_time(time))
+}
+
+/// Parse a date with a time in one of the fixed formats
+pub(crate) fn parse_absolute(s: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%d.%m.%Y-%H:%M", "%d.%m.%Y %H:%M"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+}
+
+/// Parse a time of the day like `14:30`, `14:30:00`, `14:30h` or `1430`
+pub(crate) fn parse_clock_time(s: &str) -> Option<NaiveTime> {
+    ["%H:%M", "%H:%M:%S", "%H:%Mh", "%H%M", "%H%Mh"]
+        .iter()
+        .find_map(|f| NaiveTime::parse_from_str(s, f).ok())
+}
+
+/// Parse a day relative to `today` (`today`, `yesterday`, `monday`, `last friday`) or a date
+fn parse_day(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let days_back = match s {
+        "today" => 0,
+        "yesterday" => 1,
+        _ => match s.strip_prefix("last ") {
+            // the weekday of the week before if it is today
+            Some(weekday) => match days_since(weekday.parse().ok()?, today.weekday()) {
+                0 => 7,
+                days => days as u64,
+            },
+            None => match s.parse::<Weekday>() {
+                Ok(weekday) => days_since(weekday, today.weekday()) as u64,
+                Err(_) => return parse_date(s),
+            },
+        },
+    };
+    today.checked_sub_days(Days::new(days_back))
+}
+
+/// Parse a date like `2015-09-18` or `18.09.2015`
+pub(crate) fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .or(NaiveDate::parse_from_str(s, "%d.%m.%Y"))
+        .ok()
+}
+
+/// Number of days from the last `weekday` to `today` (0 if it is today)
+fn days_since(weekday: Weekday, today: Weekday) -> u32 {
+    (today.num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7
+}
+
+/// Parse a lowercase duration, unless it is too long to represent
+fn parse_lowercase_duration(s: &str) -> Option<chrono::Duration> {
+    let re = Regex::new(r"^\s*(\d+)\s*([a-z]+)").unwrap();
+    let mut rest = s.trim();
+    let mut minutes: i64 = 0;
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let captures = re.captures(rest)?;
+        let factor = match &captures[2] {
+            "m" | "min" | "mins" | "minute" | "minutes" => 1,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60,
+            "d" | "day" | "days" => 24 * 60,
+            _ => return None,
+        };
+        let count: i64 = captures[1].parse().ok()?;
+        minutes = count.checked_mul(factor)?.checked_add(minutes)?;
+        rest = rest[captures[0].len()..].trim_start();
+    }
+    // keeps the duration far within the range chrono can represent
+    i32::try_from(minutes).ok()
+        .map(|m| chrono::Duration::minutes(m as i64))
+}
+
+/// The last time `time` occurred before `now` (today if it already passed, else yesterday)
+fn last_occurrence(time: NaiveTime, now: NaiveDateTime) -> NaiveDateTime {
+    let date = if now.time() > time {
+        now.date()
+    } else {
+        now.date().checked_sub_days(Days::new(1)).unwrap()
+    };
+    NaiveDateTime::new(date, time)
+}
+
+/// Local time of a date and time like `2024-01-15 09:00`
+#[cfg(test)]
+pub(crate) fn at(s: &str) -> DateTime<Local> {
+    local_datetime(&NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    /// Parse `s` at `now` to local time
+    fn parse_at(s: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        parse_datetime_at(s, now).map(|t| local_datetime(&t).unwrap())
+    }
+
+    /// Parse `s` on Wednesday, 2024-01-17 at 10:00
+    fn parse(s: &str) -> Option<DateTime<Local>> {
+        parse_at(s, at("2024-01-17 10:00"))
+    }
+
+    #[test]
+    fn absolute_times() {
+        assert_eq!(parse("2015-09-18 14:30"), Some(at("2015-09-18 14:30")));
+        assert_eq!(parse("2015-09-18T14:30:00"), Some(at("2015-09-18 14:30")));
+        assert_eq!(parse("18.09.2015 14:30"), Some(at("2015-09-18 14:30")));
+        assert_eq!(parse("2015-09-18"), Some(at("2015-09-18 00:00")));
+    }
+
+    #[test]
+    fn times_relative_to_now() {
+        assert_eq!(parse("now"), Some(at("2024-01-17 10:00")));
+        assert_eq!(parse("-30m"), Some(at("2024-01-17 09:30")));
+        assert_eq!(parse("2h ago"), Some(at("2024-01-17 08:00")));
+        assert_eq!(parse("1h30m  AGO"), Some(at("2024-01-17 08:30")));
+        assert_eq!(parse("-1 day"), Some(at("2024-01-16 10:00")));
+        assert_eq!(parse("-30x"), None);
+        assert_eq!(parse("ago"), None);
+    }
+
+    #[test]
+    fn days_relative_to_today() {
+        assert_eq!(parse("yesterday 17:00"), Some(at("2024-01-16 17:00")));
+        assert_eq!(parse("today 9:30"), Some(at("2024-01-17 09:30")));
+        assert_eq!(parse("yesterday"), Some(at("2024-01-16 00:00")));
+        assert_eq!(parse("someday 17:00"), None);
+    }
+
+    #[test]
+    fn weekdays_refer_to_the_last_one() {
+        assert_eq!(parse("monday 14:00"), Some(at("2024-01-15 14:00")));
+        assert_eq!(parse("Thursday"), Some(at("2024-01-11 00:00")));
+        assert_eq!(parse("wed"), Some(at("2024-01-17 00:00")));
+        assert_eq!(parse("last wednesday"), Some(at("2024-01-10 00:00")));
+        assert_eq!(parse("last friday 8:00"), Some(at("2024-01-12 08:00")));
+    }
+
+    #[test]
+    fn times_without_a_day_refer_to_their_last_occurrence() {
+        assert_eq!(parse("09:00"), Some(at("2024-01-17 09:00")));
+        assert_eq!(parse("1430"), Some(at("2024-01-16 14:30")));
+        assert_eq!(parse("10:00"), Some(at("2024-01-16 10:00")));
+        assert_eq!(parse("24:00"), None);
+    }
+
+    #[test]
+    fn midnight_and_the_turn_of_the_year() {
+        let now = at("2024-01-01 00:30");
+        assert_eq!(parse_at("00:00", now), Some(at("2024-01-01 00:00")));
+        assert_eq!(parse_at("23:00", now), Some(at("2023-12-31 23:00")));
+        assert_eq!(parse_at("yesterday 23:00", now), Some(at("2023-12-31 23:00")));
+        assert_eq!(parse_at("-1h", now), Some(at("2023-12-31 23:30")));
+        assert_eq!(parse_at("monday", now), Some(at("2024-01-01 00:00")));
+        assert_eq!(parse_at("00:00", at("2024-01-01 00:00")), Some(at("2023-12-31 00:00")));
+    }
+
+    #[test]
+    fn relative_times_go_back_in_real_time_across_daylight_saving_changes() {
+        // 03:10 in central Europe, right after the clocks were put forward from 02:00 to 03:00
+        let now = Utc.with_ymd_and_hms(2024, 3, 31, 1, 10, 0).unwrap().with_timezone(&Local);
+        for (s, minutes) in [("-30m", 30), ("2h ago", 120), ("1 day ago", 24 * 60)] {
+            let time = parse_datetime_at(s, now).unwrap();
+            assert_eq!(local_datetime(&time), Ok(now - chrono::Duration::minutes(minutes)), "{}", s);
+        }
+        if now == at("2024-03-31 03:10") {
+            assert_eq!(parse_at("-30m", now), Some(at("2024-03-31 01:40")));
+        }
+    }
+
+    #[test]
+    fn end_times_after_the_start() {
+        let naive = |s| at(s).naive_local();
+        let start = naive("2024-01-15 22:00");
+        assert_eq!(parse_end_time("23:30", start), Ok(naive("2024-01-15 23:30")));
+        assert_eq!(parse_end_time("01:00", start), Ok(naive("2024-01-16 01:00")));
+        assert_eq!(parse_end_time("22:00", start), Ok(naive("2024-01-15 22:00")));
+        assert_eq!(parse_end_time("2024-01-17 08:00", start), Ok(naive("2024-01-17 08:00")));
+    }
+
+    #[test]
+    fn time_ranges() {
+        let naive = |s| at(s).naive_local();
+        assert_eq!(parse_time_range("2024-01-15 09:00-12:30"), Ok((naive("2024-01-15 09:00"), naive("2024-01-15 12:30"))));
+        assert_eq!(parse_time_range("2024-01-15 22:00-01:00"), Ok((naive("2024-01-15 22:00"), naive("2024-01-16 01:00"))));
+        assert_eq!(parse_time_range("2024-01-15 22:00-2024-01-16 06:00"), Ok((naive("2024-01-15 22:00"), naive("2024-01-16 06:00"))));
+        assert!(parse_time_range("09:00").is_err());
+        assert!(parse_time_range("09:00-later").is_err());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("15m"), Ok(chrono::Duration::minutes(15)));
+        assert_eq!(parse_duration("1h 30min"), Ok(chrono::Duration::minutes(90)));
+        assert_eq!(parse_duration("3 Days"), Ok(chrono::Duration::days(3)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("15").is_err());
+        assert!(parse_duration("99999999999d").is_err());
+    }
+
+    #[test]
+    fn hours_are_converted_to_minutes() {
+        assert_eq!(duration_from_hours(2.5), Ok(chrono::Duration::minutes(150)));
+        assert_eq!(duration_from_hours(0.25), Ok(chrono::Duration::minutes(15)));
+    }
+
+    #[test]
+    fn hours_of_timesheets() {
+        assert_eq!(parse_hours("1.5"), Ok(chrono::Duration::minutes(90)));
+        assert_eq!(parse_hours("2:15"), Ok(chrono::Duration::minutes(135)));
+        for hours in ["", "1:75", "-2:00", "1e30", "999999999999:00", "nan", "2h"] {
+            assert!(parse_hours(hours).is_err(), "{}", hours);
+        }
+    }
+
+    #[test]
+    fn hours_have_to_be_positive_and_finite() {
+        for hours in [0.0, -1.5, f64::INFINITY, f64::NEG_INFINITY, f64::NAN, 1e20] {
+            assert!(duration_from_hours(hours).is_err(), "{}", hours);
+        }
+    }
+}
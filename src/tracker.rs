@@ -0,0 +1,1157 @@
+use chrono::offset::Local;
+use chrono::{DateTime, NaiveDate};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::*;
+use std::str::FromStr;
+
+use crate::absence::{Absence, AbsenceKind, VacationSummary};
+use crate::duration::HumanDuration;
+use crate::entry::{merge_tags, Break, ClockinTimestamp, Entry};
+use crate::schedule::Schedule;
+use crate::settings::NamespaceSettings;
+use crate::storage::{cache_file, Storage, StorageKind};
+use crate::summary::Balance;
+use crate::time::now;
+
+/// Details to add to the entry when stopping a clock
+///
+/// The comment and tags are added to the ones given at clockin, project and task replace those of the clock if set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClockoutDetails {
+    pub comment: Option<String>,
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+    pub task: Option<String>,
+}
+
+/// State of the clock of a namespace
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ClockStatus {
+    pub namespace: String,
+    pub running: bool,
+    pub start_time: Option<DateTime<Local>>,
+
+    #[serde(rename = "elapsed_minutes", serialize_with = "serialize_elapsed")]
+    pub elapsed: Option<HumanDuration>,
+
+    /// Elapsed time without breaks
+    #[serde(rename = "net_elapsed_minutes", serialize_with = "serialize_elapsed")]
+    pub net_elapsed: Option<HumanDuration>,
+
+    pub paused_since: Option<DateTime<Local>>,
+
+    pub comment: Option<String>,
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+    pub task: Option<String>,
+}
+
+/// Overview of a namespace
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct NamespaceInfo {
+    pub namespace: String,
+    pub entries: usize,
+    pub first_entry: Option<DateTime<Local>>,
+    pub last_entry: Option<DateTime<Local>>,
+    pub running: bool,
+}
+
+/// How to handle overlapping entries when merging namespaces
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OverlapResolution {
+    /// Refuse to merge
+    Abort,
+
+    /// Drop the overlapping entries of the namespace that is merged
+    KeepTarget,
+
+    /// Drop the overlapping entries of the namespace that is merged into
+    KeepSource,
+}
+
+/// Number of entries moved and dropped by [Namespace::merge_into]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MergeSummary {
+    pub merged: usize,
+    pub dropped: usize,
+}
+
+/// What happened to an entry passed to [Namespace::import]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// The entry was (or, in a dry run, would be) added
+    Added,
+
+    /// The entry is invalid, e.g. because it does not end after it starts
+    Invalid(String),
+
+    /// An entry with the same timespan already exists
+    Duplicate,
+
+    /// The entry overlaps an existing one
+    Overlapping,
+}
+
+/// Entry point to the time tracking data
+pub struct Tracker {
+    storage: Box<dyn Storage>,
+    kind: Option<StorageKind>,
+    single_clock: bool,
+}
+
+/// A single namespace of a [Tracker] with its entries and running clock
+pub struct Namespace<'a> {
+    tracker: &'a Tracker,
+    name: String,
+}
+
+impl Tracker {
+    /// Open the tracker data in the XDG data directory
+    ///
+    /// The storage backend is detected from the files present in the directory.
+    pub fn new() -> Result<Self, String> {
+        let kind = StorageKind::detect()?;
+        Ok(Tracker { storage: kind.open()?, kind: Some(kind), single_clock: false })
+    }
+
+    /// Open the tracker data in a custom storage backend
+    pub fn with_storage(storage: impl Storage + 'static) -> Self {
+        Tracker { storage: Box::new(storage), kind: None, single_clock: false }
+    }
+
+    /// Forbid starting a clock while another namespace has one running
+    pub fn with_single_clock(mut self, single_clock: bool) -> Self {
+        self.single_clock = single_clock;
+        self
+    }
+
+    /// Move all data to another storage backend in the XDG data directory
+    ///
+    /// All namespaces stay locked until the data is moved. Returns the names of the migrated namespaces.
+    pub fn migrate(&mut self, kind: StorageKind) -> Result<Vec<String>, String> {
+        if self.kind == Some(kind) {
+            return Err(format!("Data is already stored as {}", kind));
+        }
+
+        let _locks = self.storage.namespaces()?.iter()
+            .map(|namespace| self.storage.lock(namespace))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (target, namespaces) = kind.create_from(self.storage())?;
+        let source = std::mem::replace(&mut self.storage, target);
+        self.kind = Some(kind);
+        source.destroy()?;
+        Ok(namespaces)
+    }
+
+    /// Names of all namespaces with entries, a running clock, settings or absences
+    pub fn namespaces(&self) -> Result<Vec<String>, String> {
+        let mut namespaces = self.storage.namespaces()?;
+        namespaces.sort();
+        namespaces.dedup();
+        Ok(namespaces)
+    }
+
+    /// Names of all namespaces with a running clock
+    pub fn running_namespaces(&self) -> Result<Vec<String>, String> {
+        let mut running = Vec::new();
+        for namespace in self.namespaces()? {
+            if self.storage.clockin(&namespace)?.is_some() {
+                running.push(namespace);
+            }
+        }
+        Ok(running)
+    }
+
+    /// Access a namespace
+    pub fn namespace(&self, name: &str) -> Namespace<'_> {
+        Namespace { tracker: self, name: name.to_owned() }
+    }
+
+    /// The storage backend of the tracker
+    pub fn storage(&self) -> &dyn Storage {
+        self.storage.as_ref()
+    }
+
+    /// Kind of the storage backend, if it is one of the built-in persistent ones
+    pub fn storage_kind(&self) -> Option<StorageKind> {
+        self.kind
+    }
+
+    /// Absolute path to the data directory
+    pub fn data_dir(&self) -> Result<PathBuf, String> {
+        self.storage.data_dir().ok_or("Path not found".to_owned())
+    }
+}
+
+impl Namespace<'_> {
+    /// Name of the namespace
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn storage(&self) -> &dyn Storage {
+        self.tracker.storage()
+    }
+
+    /// Whether the namespace has any entries, a running clock, settings or absences
+    pub fn exists(&self) -> Result<bool, String> {
+        Ok(self.storage().entries(&self.name)?.is_some()
+            || self.running_clock()?.is_some()
+            || self.storage().settings(&self.name)?.is_some()
+            || self.storage().absences(&self.name)?.is_some())
+    }
+
+    /// Number of entries, first and last entry and whether a clock is running
+    pub fn info(&self) -> Result<NamespaceInfo, String> {
+        let entries = self.storage().entries(&self.name)?.unwrap_or_default();
+        if entries.is_empty() && !self.exists()? {
+            return Err(format!("Namespace '{}' does not exist", self.name));
+        }
+
+        Ok(NamespaceInfo {
+            namespace: self.name.clone(),
+            entries: entries.len(),
+            first_entry: entries.iter().map(|e| e.start_time).min(),
+            last_entry: entries.iter().map(|e| e.end_time).max(),
+            running: self.running_clock()?.is_some(),
+        })
+    }
+
+    /// Absolute path to the file containing the entries of this namespace
+    pub fn entries_file(&self) -> Result<PathBuf, String> {
+        self.storage().entries_file(&self.name)
+            .ok_or(format!("No entry file found for namespace '{}'", self.name))
+    }
+
+    /// Absolute path to the file containing the running clock of this namespace
+    pub fn clockin_file(&self) -> Result<PathBuf, String> {
+        self.storage().clockin_file(&self.name)
+            .ok_or(format!("No clockin file found for namespace '{}'", self.name))
+    }
+
+    /// Path to edit the namespace at, in the XDG cache directory next to the lock files
+    pub fn edit_file(&self) -> Result<PathBuf, String> {
+        cache_file(PathBuf::from(&self.name).with_extension("edit.yaml"))
+    }
+
+    /// All entries of the namespace sorted by time
+    ///
+    /// Fails if the namespace does not have any entries yet.
+    pub fn entries(&self) -> Result<Vec<Entry>, String> {
+        let mut entries = self.storage().entries(&self.name)?
+            .ok_or(format!("No file found for namespace '{}'", self.name))?;
+        entries.sort();
+        Ok(entries)
+    }
+
+    /// The currently running clock, if any
+    pub fn running_clock(&self) -> Result<Option<ClockinTimestamp>, String> {
+        self.storage().clockin(&self.name)
+    }
+
+    /// Settings of the namespace (the defaults if none were made)
+    pub fn settings(&self) -> Result<NamespaceSettings, String> {
+        Ok(self.storage().settings(&self.name)?.unwrap_or_default())
+    }
+
+    /// Change the settings of the namespace
+    pub fn update_settings(&self, update: impl FnOnce(&mut NamespaceSettings) -> Result<(), String>) -> Result<NamespaceSettings, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        let mut settings = self.settings()?;
+        update(&mut settings)?;
+        self.storage().write_settings(&self.name, &settings)?;
+        Ok(settings)
+    }
+
+    /// Working days and expected hours according to the target, absences and public holidays
+    pub fn schedule(&self) -> Result<Schedule, String> {
+        let settings = self.settings()?;
+        Ok(Schedule::new(settings.target)
+            .with_absences(&self.absences()?)
+            .with_holidays(settings.holidays))
+    }
+
+    /// Worked and expected hours from the start of the target up to and including `until`
+    pub fn balance(&self, until: NaiveDate) -> Result<Balance, String> {
+        let entries = self.storage().entries(&self.name)?.unwrap_or_default();
+        Balance::compute(&self.schedule()?, &entries, until)
+            .ok_or(format!("No target set for namespace '{}'", self.name))
+    }
+
+    /// All absences of the namespace sorted by date
+    pub fn absences(&self) -> Result<Vec<Absence>, String> {
+        let mut absences = self.storage().absences(&self.name)?.unwrap_or_default();
+        absences.sort();
+        Ok(absences)
+    }
+
+    /// Record an absence for every day from `from` to `to`
+    ///
+    /// Days of a range that are no working days (see [Schedule::is_working_day]) are skipped.
+    pub fn add_absences(&self, from: NaiveDate, to: NaiveDate, kind: AbsenceKind, comment: Option<String>) -> Result<Vec<Absence>, String> {
+        if to < from {
+            return Err(format!("The absence ends ({}) before it starts ({})", to, from));
+        }
+        let _lock = self.storage().lock(&self.name)?;
+        let schedule = self.schedule()?;
+
+        let mut absences = self.absences()?;
+        let added: Vec<Absence> = from.iter_days()
+            .take_while(|d| *d <= to)
+            .filter(|d| from == to || schedule.is_working_day(*d))
+            .map(|d| Absence::new(d, kind.clone(), comment.clone()))
+            .collect();
+        if added.is_empty() {
+            return Err(format!("No working days between {} and {}", from, to));
+        }
+        if let Some(existing) = absences.iter().find(|a| added.iter().any(|n| n.date == a.date)) {
+            return Err(format!("Namespace '{}' already has an absence on {} ({})", self.name, existing.date, existing.kind));
+        }
+
+        absences.extend(added.iter().cloned());
+        absences.sort();
+        self.storage().write_absences(&self.name, &absences)?;
+        Ok(added)
+    }
+
+    /// Remove all absences from `from` to `to` and return them
+    pub fn remove_absences(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Absence>, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        let (removed, kept): (Vec<Absence>, Vec<Absence>) = self.absences()?
+            .into_iter()
+            .partition(|a| a.date >= from && a.date <= to);
+        if removed.is_empty() {
+            return Err(format!("No absences found for namespace '{}' between {} and {}", self.name, from, to));
+        }
+        self.storage().write_absences(&self.name, &kept)?;
+        Ok(removed)
+    }
+
+    /// Vacation days taken in `year` compared to the quota of the namespace
+    pub fn vacation(&self, year: i32) -> Result<VacationSummary, String> {
+        Ok(VacationSummary::create(year, &self.absences()?, self.settings()?.vacation_days))
+    }
+
+    /// State of the clock of the namespace
+    pub fn status(&self) -> Result<ClockStatus, String> {
+        let clock = self.running_clock()?;
+        Ok(ClockStatus {
+            namespace: self.name.clone(),
+            running: clock.is_some(),
+            start_time: clock.as_ref().map(|c| c.start_time),
+            elapsed: clock.as_ref().map(|c| c.elapsed()),
+            net_elapsed: clock.as_ref().map(|c| c.net_elapsed()),
+            paused_since: clock.as_ref().and_then(|c| c.paused_since),
+            comment: clock.as_ref().and_then(|c| c.comment.clone()),
+            tags: clock.as_ref().map(|c| c.tags.clone()).unwrap_or_default(),
+            project: clock.as_ref().and_then(|c| c.project.clone()),
+            task: clock.and_then(|c| c.task),
+        })
+    }
+
+    /// Abort the currently running clock and return its timestamp
+    pub fn abort(&self) -> Result<ClockinTimestamp, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        let timestamp = self.running_clock()?
+            .ok_or(format!("Clock is not running for namespace '{}'", self.name))?;
+        self.storage().remove_clockin(&self.name)?;
+        Ok(timestamp)
+    }
+
+    /// Add a new entry, making sure it does not overlap any existing ones
+    pub fn clock(&self, entry: Entry) -> Result<Entry, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        self.add_entry(entry)
+    }
+
+    /// Start a new clock
+    ///
+    /// Fails if the clock would start in the future.
+    pub fn clockin(&self, timestamp: ClockinTimestamp) -> Result<ClockinTimestamp, String> {
+        validate_clock(&timestamp)?;
+        let _lock = self.storage().lock(&self.name)?;
+        self.check_clockin(None)?;
+        self.storage().write_clockin(&self.name, &timestamp)?;
+        Ok(timestamp)
+    }
+
+    /// Stop the running clock and start `next` in the namespace `target` at the same instant
+    ///
+    /// The clock of this namespace is stopped at the start time of `next`.
+    pub fn switch_to(&self, target: &str, details: ClockoutDetails, next: ClockinTimestamp) -> Result<(Entry, ClockinTimestamp), String> {
+        if target == self.name {
+            return Err(format!("Clock is already running for namespace '{}'", self.name));
+        }
+        validate_clock(&next)?;
+        let _lock = self.storage().lock(&self.name)?;
+        let _target_lock = self.storage().lock(target)?;
+        let target = self.tracker.namespace(target);
+        target.check_clockin(Some(&self.name))?;
+
+        let entry = self.finish_clock(details, next.start_time)?;
+        self.storage().write_clockin(&target.name, &next)?;
+        Ok((entry, next))
+    }
+
+    /// Change the running clock with `amend` and store the result
+    ///
+    /// Fails if the amended clock would start in the future or after its first break.
+    pub fn amend_running(&self, amend: impl FnOnce(&mut ClockinTimestamp) -> Result<(), String>) -> Result<ClockinTimestamp, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        let mut clock = self.running_clock()?
+            .ok_or(format!("Clock is not running for namespace '{}'", self.name))?;
+        amend(&mut clock)?;
+        validate_clock(&clock)?;
+        self.storage().write_clockin(&self.name, &clock)?;
+        Ok(clock)
+    }
+
+    /// Pause the running clock
+    pub fn pause(&self) -> Result<ClockinTimestamp, String> {
+        self.amend_running(|clock| clock.pause(now()))
+    }
+
+    /// Resume the paused clock
+    pub fn resume(&self) -> Result<ClockinTimestamp, String> {
+        self.amend_running(|clock| clock.resume(now()))
+    }
+
+    /// Stop the running clock and add the resulting entry
+    ///
+    /// If the clock is paused, the entry ends when the pause started.
+    pub fn clockout(&self, details: ClockoutDetails) -> Result<Entry, String> {
+        self.clockout_at(details, now())
+    }
+
+    /// Stop the running clock at `end_time` (for when it was forgotten) and add the resulting entry
+    ///
+    /// If the clock is paused, the entry ends when the pause started at the latest.
+    pub fn clockout_at(&self, details: ClockoutDetails, end_time: DateTime<Local>) -> Result<Entry, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        self.finish_clock(details, end_time)
+    }
+
+    /// Add multiple entries at once, skipping invalid entries, duplicates and entries that overlap existing ones
+    ///
+    /// Returns the outcome for each entry in the given order. With `dry_run` nothing is written.
+    pub fn import(&self, entries: Vec<Entry>, dry_run: bool) -> Result<Vec<ImportOutcome>, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        let mut all_entries = self.storage().entries(&self.name)?.unwrap_or_default();
+        let mut outcomes = Vec::new();
+
+        for entry in entries {
+            let outcome = if let Err(e) = validate_entry(&entry) {
+                ImportOutcome::Invalid(e)
+            } else if all_entries.iter().any(|e| e.same_span(&entry)) {
+                ImportOutcome::Duplicate
+            } else if all_entries.iter().any(|e| e.overlaps(&entry)) {
+                ImportOutcome::Overlapping
+            } else {
+                all_entries.push(entry);
+                ImportOutcome::Added
+            };
+            outcomes.push(outcome);
+        }
+
+        if !dry_run && outcomes.contains(&ImportOutcome::Added) {
+            all_entries.sort();
+            self.storage().write_entries(&self.name, &all_entries)?;
+        }
+        Ok(outcomes)
+    }
+
+    /// Move all entries, the running clock, the settings and absences to a new namespace
+    pub fn rename(&self, new_name: &str) -> Result<(), String> {
+        validate_name(new_name)?;
+        let _lock = self.storage().lock(&self.name)?;
+        let _target_lock = self.storage().lock(new_name)?;
+        if !self.exists()? {
+            return Err(format!("Namespace '{}' does not exist", self.name));
+        }
+        if self.tracker.namespace(new_name).exists()? {
+            return Err(format!("Namespace '{}' already exists.\nPlease merge the namespaces instead.", new_name));
+        }
+
+        if let Some(entries) = self.storage().entries(&self.name)? {
+            self.storage().write_entries(new_name, &entries)?;
+        }
+        if let Some(clock) = self.running_clock()? {
+            self.storage().write_clockin(new_name, &clock)?;
+        }
+        if let Some(settings) = self.storage().settings(&self.name)? {
+            self.storage().write_settings(new_name, &settings)?;
+        }
+        if let Some(absences) = self.storage().absences(&self.name)? {
+            self.storage().write_absences(new_name, &absences)?;
+        }
+        self.storage().remove_namespace(&self.name)
+    }
+
+    /// Move all entries and the running clock into another namespace and remove this one
+    ///
+    /// Entries overlapping one of the other namespace are handled according to `resolution`.
+    /// The settings of the other namespace are kept, unless it has none.
+    /// Absences are added to the ones of the other namespace, which take precedence on the same day.
+    pub fn merge_into(&self, target: &str, resolution: OverlapResolution) -> Result<MergeSummary, String> {
+        validate_name(target)?;
+        if target == self.name {
+            return Err("Can not merge a namespace into itself".to_owned());
+        }
+        let _lock = self.storage().lock(&self.name)?;
+        let _target_lock = self.storage().lock(target)?;
+        if !self.exists()? {
+            return Err(format!("Namespace '{}' does not exist", self.name));
+        }
+
+        let clock = self.running_clock()?;
+        if clock.is_some() && self.storage().clockin(target)?.is_some() {
+            return Err(format!("Clocks are running for both '{}' and '{}'.\nPlease stop one of them first.", self.name, target));
+        }
+
+        let mut source = self.storage().entries(&self.name)?.unwrap_or_default();
+        source.iter().try_for_each(validate_entry)
+            .map_err(|e| format!("{} in namespace '{}'.\nPlease fix it with `foliot edit` first.", e, self.name))?;
+        let mut target_entries = self.storage().entries(target)?.unwrap_or_default();
+        let overlapping = source.iter()
+            .filter(|s| target_entries.iter().any(|t| s.overlaps(t)))
+            .count();
+
+        let mut dropped = 0;
+        if overlapping > 0 {
+            match resolution {
+                OverlapResolution::Abort => return Err(format!(
+                    "{} entries of '{}' overlap entries of '{}'.\nPlease choose which ones to keep.",
+                    overlapping, self.name, target
+                )),
+                OverlapResolution::KeepTarget => {
+                    source.retain(|s| !target_entries.iter().any(|t| s.overlaps(t)));
+                    dropped = overlapping;
+                },
+                OverlapResolution::KeepSource => {
+                    let before = target_entries.len();
+                    target_entries.retain(|t| !source.iter().any(|s| s.overlaps(t)));
+                    dropped = before - target_entries.len();
+                },
+            }
+        }
+
+        let merged = source.len();
+        target_entries.append(&mut source);
+        target_entries.sort();
+        self.storage().write_entries(target, &target_entries)?;
+        if let Some(clock) = clock {
+            self.storage().write_clockin(target, &clock)?;
+        }
+        if let Some(settings) = self.storage().settings(&self.name)? {
+            if self.storage().settings(target)?.is_none() {
+                self.storage().write_settings(target, &settings)?;
+            }
+        }
+        if let Some(absences) = self.storage().absences(&self.name)? {
+            let mut target_absences = self.storage().absences(target)?.unwrap_or_default();
+            let new_absences: Vec<Absence> = absences.into_iter()
+                .filter(|a| !target_absences.iter().any(|t| t.date == a.date))
+                .collect();
+            target_absences.extend(new_absences);
+            target_absences.sort();
+            self.storage().write_absences(target, &target_absences)?;
+        }
+        self.storage().remove_namespace(&self.name)?;
+        Ok(MergeSummary { merged, dropped })
+    }
+
+    /// Remove all entries, the running clock, the settings and absences of the namespace
+    pub fn delete(&self) -> Result<(), String> {
+        let _lock = self.storage().lock(&self.name)?;
+        if !self.exists()? {
+            return Err(format!("Namespace '{}' does not exist", self.name));
+        }
+        self.storage().remove_namespace(&self.name)
+    }
+
+    /// Entry with an ID starting with `id`
+    pub fn entry(&self, id: &str) -> Result<Entry, String> {
+        let entries = self.entries()?;
+        let index = find_entry(&entries, id)?;
+        Ok(entries[index].clone())
+    }
+
+    /// Remove the entry with an ID starting with `id`
+    pub fn delete_entry(&self, id: &str) -> Result<Entry, String> {
+        self.modify_entries(|entries| {
+            let index = find_entry(entries, id)?;
+            Ok((entries.remove(index), Vec::new()))
+        })
+    }
+
+    /// Change the entry with an ID starting with `id` with `amend`
+    ///
+    /// The entry keeps its ID and must not overlap any other entry afterwards.
+    pub fn amend_entry(&self, id: &str, amend: impl FnOnce(&mut Entry)) -> Result<Entry, String> {
+        self.modify_entries(|entries| {
+            let index = find_entry(entries, id)?;
+            let mut entry = entries[index].clone().with_fixed_id();
+            amend(&mut entry);
+            validate_entry(&entry)?;
+            entries[index] = entry.clone();
+            Ok((entry, vec![index]))
+        })
+    }
+
+    /// Split the entry with an ID starting with `id` into two at `time`
+    ///
+    /// The first part keeps the ID, a break around `time` is split as well.
+    pub fn split_entry(&self, id: &str, time: DateTime<Local>) -> Result<(Entry, Entry), String> {
+        self.modify_entries(|entries| {
+            let index = find_entry(entries, id)?;
+            let entry = entries[index].clone().with_fixed_id();
+            if time <= entry.start_time || time >= entry.end_time {
+                return Err(format!("{} is not within the entry", time));
+            }
+
+            let mut first = entry.clone();
+            first.end_time = time;
+            first.breaks = entry.breaks.iter()
+                .filter(|b| b.start_time < time)
+                .map(|b| Break { start_time: b.start_time, end_time: b.end_time.min(time) })
+                .collect();
+
+            let mut second = entry.clone();
+            second.start_time = time;
+            second.breaks = entry.breaks.iter()
+                .filter(|b| b.end_time > time)
+                .map(|b| Break { start_time: b.start_time.max(time), end_time: b.end_time })
+                .collect();
+            let second = second.with_new_id();
+
+            entries[index] = first.clone();
+            entries.push(second.clone());
+            Ok(((first, second), Vec::new()))
+        })
+    }
+
+    /// Join two entries into one spanning both, the time between them becoming a break
+    ///
+    /// The joined entry keeps the ID of the earlier one. Comments and tags are combined, project
+    /// and task have to match if both entries have one.
+    pub fn join_entries(&self, id: &str, other_id: &str) -> Result<Entry, String> {
+        self.modify_entries(|entries| {
+            let index = find_entry(entries, id)?;
+            let other_index = find_entry(entries, other_id)?;
+            if index == other_index {
+                return Err("Can not join an entry with itself".to_owned());
+            }
+            let (first_index, second_index) = if entries[index] <= entries[other_index] {
+                (index, other_index)
+            } else {
+                (other_index, index)
+            };
+            let first = entries[first_index].clone().with_fixed_id();
+            let second = entries[second_index].clone();
+
+            let combine = |a: &Option<String>, b: &Option<String>, what: &str| match (a, b) {
+                (Some(a), Some(b)) if a != b => Err(format!("Entries belong to different {}s ('{}' and '{}')", what, a, b)),
+                (a, b) => Ok(a.clone().or(b.clone())),
+            };
+            let project = combine(&first.project, &second.project, "project")?;
+            let task = combine(&first.task, &second.task, "task")?;
+            let comment = match (&first.comment, &second.comment) {
+                (Some(a), Some(b)) if a != b => Some(format!("{}; {}", a, b)),
+                (a, b) => a.clone().or(b.clone()),
+            };
+
+            let mut joined = first.clone();
+            joined.end_time = first.end_time.max(second.end_time);
+            joined.comment = comment;
+            joined.project = project;
+            joined.task = task;
+            merge_tags(&mut joined.tags, second.tags.clone());
+            joined.breaks.extend(second.breaks.iter().copied());
+            if second.start_time > first.end_time {
+                joined.breaks.push(Break { start_time: first.end_time, end_time: second.start_time });
+            }
+            joined.breaks.sort();
+
+            entries[first_index] = joined.clone();
+            entries.remove(second_index);
+            let first_index = entries.iter().position(|e| e.id() == joined.id()).unwrap();
+            Ok((joined, vec![first_index]))
+        })
+    }
+
+    /// Let the user edit all entries as YAML, storing them only if they are valid
+    ///
+    /// `edit` gets the current YAML and the error of the previous attempt, if any, and returns
+    /// the edited YAML or `None` to discard the changes. Returns whether the entries were changed.
+    pub fn edit_entries(&self, edit: impl FnMut(&str, Option<&str>) -> Result<Option<String>, String>) -> Result<bool, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        let entries = self.entries()?;
+        match edit_yaml(&entries, edit, |entries: &Vec<Entry>| validate_entries(entries))? {
+            Some(mut entries) => {
+                entries.sort();
+                self.storage().write_entries(&self.name, &entries)?;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    /// Let the user edit the running clock as YAML, storing it only if it is valid (see [Namespace::edit_entries])
+    pub fn edit_clockin(&self, edit: impl FnMut(&str, Option<&str>) -> Result<Option<String>, String>) -> Result<bool, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        let clock = self.running_clock()?
+            .ok_or(format!("Clock is not running for namespace '{}'", self.name))?;
+        match edit_yaml(&clock, edit, validate_clock)? {
+            Some(clock) => {
+                self.storage().write_clockin(&self.name, &clock)?;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    /// Make sure a clock may be started, ignoring the clock of namespace `stopping` (the caller has to hold the lock)
+    fn check_clockin(&self, stopping: Option<&str>) -> Result<(), String> {
+        if self.running_clock()?.is_some() {
+            return Err(format!("Clock is already running for namespace '{}'.\nPlease abort it before continuing.", self.name));
+        }
+
+        if self.tracker.single_clock {
+            let running = self.tracker.running_namespaces()?;
+            if let Some(other) = running.iter().find(|n| Some(n.as_str()) != stopping) {
+                return Err(format!("Clock is already running for namespace '{}'.\nPlease clock out or switch namespaces instead.", other));
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the running clock at `end_time` and add the resulting entry (the caller has to hold the lock)
+    fn finish_clock(&self, details: ClockoutDetails, end_time: DateTime<Local>) -> Result<Entry, String> {
+        let clockin_timestamp = self.running_clock()?
+            .ok_or(format!("Clock is not running for namespace '{}'", self.name))?;
+
+        let mut tags = clockin_timestamp.tags;
+        merge_tags(&mut tags, details.tags);
+        let comment = match (clockin_timestamp.comment, details.comment) {
+            (Some(start_comment), Some(end_comment)) => Some(format!("{}; {}", start_comment, end_comment)),
+            (start_comment, end_comment) => start_comment.or(end_comment),
+        };
+        if end_time <= clockin_timestamp.start_time {
+            return Err(format!("Clock can not stop at {}, which is not after it started at {}", end_time, clockin_timestamp.start_time));
+        }
+        if end_time > now() {
+            return Err(format!("Clock can not stop at {} in the future", end_time));
+        }
+        if clockin_timestamp.breaks.iter().any(|b| b.end_time > end_time) {
+            return Err(format!("Clock can not stop at {} (before the end of a break)", end_time));
+        }
+        let end_time = clockin_timestamp.paused_since.map_or(end_time, |p| p.min(end_time));
+        let entry = Entry::create(clockin_timestamp.start_time, end_time, comment, tags)
+            .with_project(details.project.or(clockin_timestamp.project))
+            .with_task(details.task.or(clockin_timestamp.task))
+            .with_breaks(clockin_timestamp.breaks);
+        let entry = self.add_entry(entry)?;
+        self.storage().remove_clockin(&self.name)?;
+        Ok(entry)
+    }
+
+    /// Change the entries of the namespace with `modify` and store them
+    ///
+    /// `modify` returns the indices of the entries it changed, which must not overlap any others.
+    fn modify_entries<T>(&self, modify: impl FnOnce(&mut Vec<Entry>) -> Result<(T, Vec<usize>), String>) -> Result<T, String> {
+        let _lock = self.storage().lock(&self.name)?;
+        let mut entries = self.entries()?;
+        let (result, changed) = modify(&mut entries)?;
+
+        for index in changed {
+            let entry = &entries[index];
+            let overlapping = entries.iter()
+                .enumerate()
+                .any(|(i, e)| i != index && e.overlaps(entry));
+            if overlapping {
+                return Err("Changed entry overlaps an existing one".to_owned());
+            }
+        }
+
+        entries.sort();
+        self.storage().write_entries(&self.name, &entries)?;
+        Ok(result)
+    }
+
+    /// Add an entry if it does not overlap any existing ones (the caller has to hold the lock)
+    fn add_entry(&self, entry: Entry) -> Result<Entry, String> {
+        validate_entry(&entry)?;
+        // check if any entry overlaps
+        if !self.storage().overlapping_entries(&self.name, &entry)?.is_empty() {
+            return Err("New entry overlaps an existing one".to_owned());
+        }
+
+        self.storage().add_entry(&self.name, &entry)?;
+        Ok(entry)
+    }
+}
+
+impl Display for OverlapResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverlapResolution::Abort => write!(f, "abort"),
+            OverlapResolution::KeepTarget => write!(f, "keep-target"),
+            OverlapResolution::KeepSource => write!(f, "keep-source"),
+        }
+    }
+}
+
+impl FromStr for OverlapResolution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abort" => Ok(OverlapResolution::Abort),
+            "keep-target" => Ok(OverlapResolution::KeepTarget),
+            "keep-source" => Ok(OverlapResolution::KeepSource),
+            _ => Err(format!("unknown resolution '{}' (expected abort, keep-target or keep-source)", s)),
+        }
+    }
+}
+
+/// Repeat `edit` on the YAML representation of `value` until it parses and passes `validate`
+fn edit_yaml<T: Serialize + DeserializeOwned>(value: &T,
+                                              mut edit: impl FnMut(&str, Option<&str>) -> Result<Option<String>, String>,
+                                              validate: impl Fn(&T) -> Result<(), String>) -> Result<Option<T>, String> {
+    let mut content = serde_yaml::to_string(value)
+        .map_err(|e| e.to_string())?;
+    let mut error = None;
+
+    loop {
+        content = match edit(&content, error.as_deref())? {
+            Some(content) => content,
+            None => return Ok(None),
+        };
+        let parsed = serde_yaml::from_str(&content)
+            .map_err(|e| e.to_string())
+            .and_then(|value| validate(&value).map(|_| value));
+        match parsed {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) => error = Some(e),
+        }
+    }
+}
+
+/// Make sure an entry ends after it starts and its breaks are within it
+fn validate_entry(entry: &Entry) -> Result<(), String> {
+    if entry.end_time <= entry.start_time {
+        return Err(format!("Entry starting at {} does not end after it starts", entry.start_time));
+    }
+    if entry.breaks.iter().any(|b| b.start_time < entry.start_time || b.end_time > entry.end_time || b.end_time < b.start_time) {
+        return Err(format!("Entry starting at {} has a break outside of it", entry.start_time));
+    }
+    Ok(())
+}
+
+/// Make sure no entry ends before it starts and no two entries overlap or share an ID
+fn validate_entries(entries: &[Entry]) -> Result<(), String> {
+    let mut sorted: Vec<&Entry> = entries.iter().collect();
+    sorted.sort();
+
+    let mut ids = HashMap::new();
+    for entry in &sorted {
+        if let Some(other) = ids.insert(entry.id(), entry) {
+            return Err(format!("Entries starting at {} and {} have the same ID", other.start_time, entry.start_time));
+        }
+    }
+
+    // the entry ending last so far overlaps any following one that overlaps an earlier entry
+    let mut latest: Option<&Entry> = None;
+    for entry in sorted {
+        validate_entry(entry)?;
+        if let Some(latest) = latest.filter(|l| l.overlaps(entry)) {
+            return Err(format!("Entry starting at {} overlaps the one starting at {}", entry.start_time, latest.start_time));
+        }
+        if latest.map_or(true, |l| entry.end_time > l.end_time) {
+            latest = Some(entry);
+        }
+    }
+    Ok(())
+}
+
+/// Make sure a clock does not start in the future or after it was paused
+fn validate_clock(clock: &ClockinTimestamp) -> Result<(), String> {
+    let first_break = clock.breaks.first().map(|b| b.start_time).or(clock.paused_since);
+    if clock.start_time > now() || first_break.is_some_and(|b| clock.start_time > b) {
+        return Err(format!("Clock can not start at {} (after it was paused or in the future)", clock.start_time));
+    }
+    Ok(())
+}
+
+/// Index of the only entry with an ID starting with `id`
+fn find_entry(entries: &[Entry], id: &str) -> Result<usize, String> {
+    let matches: Vec<usize> = entries.iter()
+        .enumerate()
+        .filter(|(_, e)| !id.is_empty() && e.id().starts_with(id))
+        .map(|(i, _)| i)
+        .collect();
+    match matches.as_slice() {
+        [index] => Ok(*index),
+        [] => Err(format!("No entry with ID '{}'", id)),
+        [first, rest @ ..] if rest.iter().all(|i| entries[*i].id() == entries[*first].id()) =>
+            Err(format!("Several entries have the ID '{}'.\nPlease change the id of one of them with `foliot edit`.", entries[*first].id())),
+        _ => Err(format!("ID '{}' is ambiguous, please give more characters", id)),
+    }
+}
+
+/// Make sure a namespace name can be used as file name
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Invalid namespace name '{}'", name));
+    }
+    Ok(())
+}
+
+fn serialize_elapsed<S: serde::Serializer>(elapsed: &Option<HumanDuration>, serializer: S) -> Result<S::Ok, S::Error> {
+    elapsed.map(|d| d.num_minutes()).serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::time::at;
+
+    fn entry(from: &str, to: &str) -> Entry {
+        Entry::create(at(from), at(to), None, Vec::new())
+    }
+
+    fn clock(from: &str) -> ClockinTimestamp {
+        ClockinTimestamp::starting(&at(from).naive_local()).unwrap()
+    }
+
+    #[test]
+    fn clockout_adds_the_running_clock_as_entry() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        namespace.clockin(clock("2024-01-15 09:00")
+            .with_comment(Some("Review".to_owned()))
+            .with_tags(vec!["a".to_owned()])).unwrap();
+        let details = ClockoutDetails {
+            comment: Some("Done".to_owned()),
+            tags: vec!["b".to_owned(), "a".to_owned()],
+            project: Some("foliot".to_owned()),
+            task: None,
+        };
+        let entry = namespace.clockout_at(details, at("2024-01-15 12:00")).unwrap();
+
+        assert_eq!(entry.start_time, at("2024-01-15 09:00"));
+        assert_eq!(entry.end_time, at("2024-01-15 12:00"));
+        assert_eq!(entry.comment.as_deref(), Some("Review; Done"));
+        assert_eq!(entry.tags, vec!["a", "b"]);
+        assert_eq!(entry.project.as_deref(), Some("foliot"));
+        assert_eq!(namespace.running_clock().unwrap(), None);
+        assert_eq!(namespace.entries().unwrap(), vec![entry]);
+    }
+
+    #[test]
+    fn clock_can_only_run_once() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        assert!(namespace.clockout_at(ClockoutDetails::default(), at("2024-01-15 12:00")).is_err());
+        namespace.clockin(clock("2024-01-15 09:00")).unwrap();
+        assert!(namespace.clockin(clock("2024-01-15 10:00")).is_err());
+        assert_eq!(namespace.running_clock().unwrap().unwrap().start_time, at("2024-01-15 09:00"));
+    }
+
+    #[test]
+    fn clock_can_not_start_in_the_future() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        let future = ClockinTimestamp::starting(&(now() + chrono::Duration::hours(1)).naive_local()).unwrap();
+        assert!(namespace.clockin(future.clone()).is_err());
+        assert_eq!(namespace.running_clock().unwrap(), None);
+
+        namespace.clockin(clock("2024-01-15 09:00")).unwrap();
+        assert!(namespace.switch_to("side", ClockoutDetails::default(), future).is_err());
+        assert_eq!(namespace.running_clock().unwrap().unwrap().start_time, at("2024-01-15 09:00"));
+        assert_eq!(tracker.namespace("side").running_clock().unwrap(), None);
+        assert!(namespace.entries().is_err());
+    }
+
+    #[test]
+    fn abort_drops_the_running_clock() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        namespace.clockin(clock("2024-01-15 09:00")).unwrap();
+        assert_eq!(namespace.abort().unwrap().start_time, at("2024-01-15 09:00"));
+        assert_eq!(namespace.running_clock().unwrap(), None);
+        assert!(namespace.entries().is_err());
+    }
+
+    #[test]
+    fn import_dry_run_does_not_write() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        namespace.clock(entry("2024-01-15 09:00", "2024-01-15 12:00")).unwrap();
+        let outcomes = namespace.import(vec![
+            entry("2024-01-16 09:00", "2024-01-16 12:00"),
+            entry("2024-01-17 09:00", "2024-01-17 12:00"),
+        ], true).unwrap();
+
+        assert_eq!(outcomes, vec![ImportOutcome::Added, ImportOutcome::Added]);
+        assert_eq!(namespace.entries().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn moved_entry_and_new_one_in_its_place_have_different_ids() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        let first = namespace.clock(entry("2024-01-15 09:00", "2024-01-15 12:00")).unwrap();
+        namespace.amend_entry(&first.id(), |e| {
+            e.start_time = at("2024-01-16 09:00");
+            e.end_time = at("2024-01-16 12:00");
+        }).unwrap();
+        let second = namespace.clock(entry("2024-01-15 09:00", "2024-01-15 12:00")).unwrap();
+
+        assert_ne!(first.id(), second.id());
+        assert_eq!(namespace.delete_entry(&first.id()).unwrap().start_time, at("2024-01-16 09:00"));
+        assert_eq!(namespace.entry(&second.id()).unwrap().start_time, at("2024-01-15 09:00"));
+    }
+
+    #[test]
+    fn amend_changes_the_entry_unless_it_overlaps() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        let first = namespace.clock(entry("2024-01-15 09:00", "2024-01-15 12:00")).unwrap();
+        let second = namespace.clock(entry("2024-01-15 13:00", "2024-01-15 17:00")).unwrap();
+
+        let amended = namespace.amend_entry(&second.id()[..7], |e| {
+            e.end_time = at("2024-01-15 18:00");
+            e.comment = Some("Release".to_owned());
+        }).unwrap();
+        assert_eq!(amended.id(), second.id());
+        assert_eq!(namespace.entry(&second.id()).unwrap(), amended);
+
+        assert!(namespace.amend_entry(&second.id(), |e| e.start_time = at("2024-01-15 11:00")).is_err());
+        assert!(namespace.amend_entry(&second.id(), |e| e.end_time = at("2024-01-15 13:00")).is_err());
+        assert_eq!(namespace.entries().unwrap(), vec![first, amended]);
+    }
+
+    #[test]
+    fn delete_removes_only_the_given_entry() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        let first = namespace.clock(entry("2024-01-15 09:00", "2024-01-15 12:00")).unwrap();
+        let second = namespace.clock(entry("2024-01-15 13:00", "2024-01-15 17:00")).unwrap();
+
+        assert_eq!(namespace.delete_entry(&first.id()[..7]).unwrap(), first);
+        assert!(namespace.delete_entry(&first.id()).is_err());
+        assert_eq!(namespace.entries().unwrap(), vec![second]);
+    }
+
+    #[test]
+    fn split_divides_the_entry_and_its_breaks() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        let entry = namespace.clock(entry("2024-01-15 09:00", "2024-01-15 17:00")
+            .with_breaks(vec![Break { start_time: at("2024-01-15 11:30"), end_time: at("2024-01-15 12:30") }])).unwrap();
+
+        assert!(namespace.split_entry(&entry.id(), at("2024-01-15 09:00")).is_err());
+        assert!(namespace.split_entry(&entry.id(), at("2024-01-15 18:00")).is_err());
+        let (first, second) = namespace.split_entry(&entry.id(), at("2024-01-15 12:00")).unwrap();
+
+        assert_eq!(first.id(), entry.id());
+        assert_ne!(second.id(), entry.id());
+        assert_eq!((first.start_time, first.end_time), (at("2024-01-15 09:00"), at("2024-01-15 12:00")));
+        assert_eq!(first.breaks, vec![Break { start_time: at("2024-01-15 11:30"), end_time: at("2024-01-15 12:00") }]);
+        assert_eq!((second.start_time, second.end_time), (at("2024-01-15 12:00"), at("2024-01-15 17:00")));
+        assert_eq!(second.breaks, vec![Break { start_time: at("2024-01-15 12:00"), end_time: at("2024-01-15 12:30") }]);
+        assert_eq!(namespace.entries().unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn join_turns_the_gap_into_a_break() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        let first = namespace.clock(entry("2024-01-15 09:00", "2024-01-15 12:00")
+            .with_project(Some("foliot".to_owned()))).unwrap();
+        let second = namespace.clock(entry("2024-01-15 13:00", "2024-01-15 17:00")).unwrap();
+        let other = namespace.clock(entry("2024-01-15 18:00", "2024-01-15 19:00")
+            .with_project(Some("other".to_owned()))).unwrap();
+
+        assert!(namespace.join_entries(&first.id(), &first.id()).is_err());
+        assert!(namespace.join_entries(&first.id(), &other.id()).is_err());
+        let joined = namespace.join_entries(&second.id(), &first.id()).unwrap();
+
+        assert_eq!(joined.id(), first.id());
+        assert_eq!((joined.start_time, joined.end_time), (at("2024-01-15 09:00"), at("2024-01-15 17:00")));
+        assert_eq!(joined.breaks, vec![Break { start_time: at("2024-01-15 12:00"), end_time: at("2024-01-15 13:00") }]);
+        assert_eq!(joined.project.as_deref(), Some("foliot"));
+        assert_eq!(namespace.entries().unwrap(), vec![joined, other]);
+    }
+
+    #[test]
+    fn import_rejects_entries_not_ending_after_they_start() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        let outcomes = namespace.import(vec![
+            entry("2024-01-15 09:00", "2024-01-15 12:00"),
+            entry("2024-01-15 14:00", "2024-01-15 12:00"),
+            entry("2024-01-15 14:00", "2024-01-15 14:00"),
+            entry("2024-01-15 09:00", "2024-01-15 12:00"),
+            entry("2024-01-15 11:00", "2024-01-15 13:00"),
+        ], false).unwrap();
+
+        assert_eq!(outcomes[0], ImportOutcome::Added);
+        assert!(matches!(outcomes[1], ImportOutcome::Invalid(_)));
+        assert!(matches!(outcomes[2], ImportOutcome::Invalid(_)));
+        assert_eq!(outcomes[3], ImportOutcome::Duplicate);
+        assert_eq!(outcomes[4], ImportOutcome::Overlapping);
+        assert_eq!(namespace.entries().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn clock_rejects_entries_not_ending_after_they_start() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        assert!(namespace.clock(entry("2024-01-15 12:00", "2024-01-15 10:00")).is_err());
+        assert!(namespace.clock(entry("2024-01-15 12:00", "2024-01-15 12:00")).is_err());
+        assert!(namespace.entries().is_err());
+    }
+
+    #[test]
+    fn clock_can_not_stop_when_it_started() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        namespace.clockin(clock("2024-01-15 09:00")).unwrap();
+
+        assert!(namespace.clockout_at(ClockoutDetails::default(), at("2024-01-15 08:00")).is_err());
+        assert!(namespace.clockout_at(ClockoutDetails::default(), at("2024-01-15 09:00")).is_err());
+        assert!(namespace.running_clock().unwrap().is_some());
+        let entry = namespace.clockout_at(ClockoutDetails::default(), at("2024-01-15 12:00")).unwrap();
+        assert_eq!(entry.end_time, at("2024-01-15 12:00"));
+    }
+
+    #[test]
+    fn entries_sharing_an_id_are_reported() {
+        let tracker = Tracker::with_storage(MemoryStorage::new());
+        let namespace = tracker.namespace("work");
+        let first = entry("2024-01-15 09:00", "2024-01-15 12:00");
+        let mut second = first.clone();
+        second.start_time = at("2024-01-16 09:00");
+        second.end_time = at("2024-01-16 12:00");
+        tracker.storage().write_entries("work", &[first.clone(), second]).unwrap();
+
+        let error = namespace.delete_entry(&first.id()).unwrap_err();
+        assert!(error.contains("foliot edit"), "{}", error);
+        let mut reported = None;
+        let saved = namespace.edit_entries(|content, error| match error {
+            Some(error) => {
+                reported = Some(error.to_owned());
+                Ok(None)
+            },
+            None => Ok(Some(content.to_owned())),
+        }).unwrap();
+        assert!(!saved);
+        assert!(reported.unwrap().contains("same ID"));
+    }
+}